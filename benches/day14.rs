@@ -0,0 +1,96 @@
+//! Compares ways of finding the first occurrence of a run of digits in the
+//! day14 recipe scoreboard: the `VecDeque` loop day14 used to use by hand,
+//! and the `lib::sliding` adaptors.
+//!
+//! Run with `cargo bench --bench day14 [DIGITS]`.
+
+extern crate lib;
+
+use lib::sliding::Sliding;
+use std::collections::VecDeque;
+use std::env;
+use std::time::Instant;
+
+/// The day14 scoreboard, yielding each recipe's score as it is created.
+struct Recipes {
+    scores: Vec<u8>,
+    elves: (usize, usize),
+    next: usize,
+}
+
+impl Recipes {
+    fn new() -> Recipes {
+        Recipes { scores: vec![3, 7], elves: (0, 1), next: 0 }
+    }
+}
+
+impl Iterator for Recipes {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.next >= self.scores.len() {
+            let Recipes { scores, elves: (e1, e2), .. } = self;
+            let (r1, r2) = (scores[*e1], scores[*e2]);
+            let sum = r1 + r2;
+            if sum >= 10 {
+                scores.push(sum / 10);
+            }
+
+            scores.push(sum % 10);
+            *e1 = (*e1 + 1 + r1 as usize) % scores.len();
+            *e2 = (*e2 + 1 + r2 as usize) % scores.len();
+        }
+
+        self.next += 1;
+        Some(self.scores[self.next - 1])
+    }
+}
+
+/// Ways of finding the offset of the first occurrence of some digits.
+type Finder = fn(&[u8]) -> usize;
+
+fn deque_loop(target: &[u8]) -> usize {
+    let sub_seq: VecDeque<u8> = target.iter().cloned().collect();
+    let mut recipes = Recipes::new();
+    let mut window: VecDeque<_> = (&mut recipes).take(sub_seq.len()).collect();
+
+    let mut i = 0;
+    while sub_seq != window {
+        window.pop_front();
+        window.push_back(recipes.next().unwrap());
+        i += 1
+    }
+
+    i
+}
+
+fn windows(target: &[u8]) -> usize {
+    Recipes::new().windows(target.len()).position(|w| w == target).unwrap()
+}
+
+fn subsequence_offsets(target: &[u8]) -> usize {
+    Recipes::new().subsequence_offsets(target.iter().cloned()).next().unwrap()
+}
+
+fn main() {
+    let digits = env::args().skip(1).find(|arg| !arg.starts_with('-'))
+        .unwrap_or_else(|| "51589101".to_owned());
+
+    let target: Vec<u8> = digits.bytes().map(|b| b - b'0').collect();
+
+    let mut expected = None;
+    let runs: [(&str, Finder); 3] = [
+        ("VecDeque loop", deque_loop),
+        ("windows", windows),
+        ("subsequence_offsets", subsequence_offsets),
+    ];
+
+    for &(name, find) in &runs {
+        let start = Instant::now();
+        let offset = find(&target);
+        let elapsed = start.elapsed();
+
+        assert_eq!(*expected.get_or_insert(offset), offset, "{} disagrees", name);
+        println!("{:<20} {:>10} in {:?}", name, offset, elapsed);
+    }
+}
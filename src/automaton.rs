@@ -0,0 +1,168 @@
+//! Cellular automata over grids.
+//!
+//! An [`Automaton`] holds two buffers: one with the current generation, which
+//! rules read from, and one that the next generation is written to.  Rules
+//! therefore always see the whole of the previous generation, however the
+//! cells around them have changed.
+//!
+//! [`Automaton`]: struct.Automaton.html
+
+use grid::Grid;
+use std::mem;
+
+/// A cell's surroundings in the current generation of an automaton.
+pub struct Neighbourhood<'g, T: 'g> {
+    grid: &'g Grid<T>,
+    pos: (usize, usize),
+}
+
+impl <'g, T> Neighbourhood<'g, T> {
+    /// Position of the cell in the grid.
+    pub fn pos(&self) -> (usize, usize) {
+        self.pos
+    }
+
+    /// The whole of the current generation.
+    pub fn grid(&self) -> &'g Grid<T> {
+        self.grid
+    }
+
+    /// The (up to) 4 cells orthogonally adjacent to this one, in reading
+    /// order.
+    pub fn cells4(&self) -> impl Iterator<Item = &'g T> {
+        let grid = self.grid;
+        grid.neighbours4(self.pos).map(move |pos| &grid[pos])
+    }
+
+    /// The (up to) 8 cells orthogonally or diagonally adjacent to this one, in
+    /// reading order.
+    pub fn cells8(&self) -> impl Iterator<Item = &'g T> {
+        let grid = self.grid;
+        grid.neighbours8(self.pos).map(move |pos| &grid[pos])
+    }
+
+    /// Number of the 8 adjacent cells satisfying `pred`.
+    pub fn count8<P>(&self, mut pred: P) -> usize
+        where P: FnMut(&T) -> bool
+    {
+        self.cells8().filter(|t| pred(t)).count()
+    }
+}
+
+/// Steps a grid through generations of a cellular automaton.
+pub struct Automaton<T> {
+    current: Grid<T>,
+    /// Buffer for the next generation, holding stale values until it is
+    /// written to.
+    next: Grid<T>,
+    generation: usize,
+}
+
+impl <T> Automaton<T> where T: Clone {
+    pub fn new(init: Grid<T>) -> Automaton<T> {
+        Automaton { next: init.clone(), current: init, generation: 0 }
+    }
+}
+
+impl <T> Automaton<T> {
+    /// The current generation.
+    pub fn grid(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.current
+    }
+
+    /// Number of generations stepped through so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Advances to the next generation, in which each cell becomes
+    /// `rule(cell, neighbourhood)`.
+    pub fn step<F>(&mut self, mut rule: F)
+        where F: FnMut(&T, &Neighbourhood<T>) -> T
+    {
+        self.advance(|_, _| {}, &mut rule);
+    }
+
+    /// As `step`, but also returns the number of cells that changed, which is
+    /// zero once the automaton has converged.
+    pub fn step_changes<F>(&mut self, mut rule: F) -> usize
+        where F: FnMut(&T, &Neighbourhood<T>) -> T,
+              T: PartialEq
+    {
+        let mut changes = 0;
+        self.advance(|prev, next| if prev != next { changes += 1 }, &mut rule);
+        changes
+    }
+
+    /// Writes the next generation, calling `written(prev, next)` for each
+    /// cell, and then swaps it into place.
+    fn advance<W, F>(&mut self, mut written: W, rule: &mut F)
+        where W: FnMut(&T, &T),
+              F: FnMut(&T, &Neighbourhood<T>) -> T
+    {
+        let Automaton { current, next, .. } = self;
+        for (pos, cell) in current.iter_coords() {
+            let value = rule(cell, &Neighbourhood { grid: current, pos });
+            written(cell, &value);
+            next[pos] = value;
+        }
+
+        mem::swap(current, next);
+        self.generation += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use automaton::{Automaton, Neighbourhood};
+    use grid::Grid;
+
+    fn life(&live: &bool, nbrs: &Neighbourhood<bool>) -> bool {
+        match nbrs.count8(|&n| n) {
+            3 => true,
+            2 => live,
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn blinker() {
+        let init = Grid::new_with_mapping(5, 5, |x, y| y == 2 && (1 ..= 3).contains(&x));
+        let mut life_grid = Automaton::new(init);
+
+        assert_eq!(life_grid.step_changes(life), 4);
+        assert_eq!(life_grid.grid().to_string(), ".....\n..#..\n..#..\n..#..\n.....\n");
+
+        life_grid.step(life);
+        assert_eq!(life_grid.grid().to_string(), ".....\n.....\n.###.\n.....\n.....\n");
+        assert_eq!(life_grid.generation(), 2);
+    }
+
+    #[test]
+    fn converges() {
+        // Erosion: cells die unless all 4 of their neighbours are alive.
+        let mut erode = Automaton::new(Grid::new(3, 3, true));
+        let rule = |&live: &bool, nbrs: &Neighbourhood<bool>| {
+            live && nbrs.cells4().filter(|&&n| n).count() == 4
+        };
+
+        // The centre survives the first generation, because its neighbours
+        // have not died yet as far as the rule can see.
+        assert_eq!(erode.step_changes(rule), 8);
+        assert_eq!(erode.step_changes(rule), 1);
+        assert_eq!(erode.step_changes(rule), 0);
+
+        let seen: Vec<(usize, usize)> = {
+            let mut seen = vec![];
+            erode.step(|_, nbrs| { seen.push(nbrs.pos()); false });
+            seen
+        };
+
+        assert_eq!(seen.len(), 9);
+        assert_eq!(seen[5], (2, 1));
+    }
+}
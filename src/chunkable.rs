@@ -40,6 +40,52 @@ impl <I, K, C> Iterator for Chunked<I, K, C>
     }
 }
 
+/// An iterator over runs of consecutive elements of its underlying iterator
+/// that have the same value of a classifier function.  Each run is yielded
+/// alongside that value.
+///
+/// Constructed by a call to [`group_by`].
+///
+/// [`group_by`]: trait.Chunkable.html#method.group_by
+pub struct Groups<I: Iterator, K, C> {
+    iter: I,
+    class: C,
+    /// The first element of the next group, and its key, if it has been read
+    /// already.
+    pending: Option<(K, I::Item)>,
+}
+
+impl <I, K, C> Iterator for Groups<I, K, C>
+    where I: Iterator,
+          K: PartialEq,
+          C: FnMut(&I::Item) -> K
+{
+    type Item = (K, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.pending.take() {
+            Some(pending) => pending,
+            None => {
+                let item = self.iter.next()?;
+                ((self.class)(&item), item)
+            },
+        };
+
+        let mut group = vec![first];
+        for item in &mut self.iter {
+            let next = (self.class)(&item);
+            if next == key {
+                group.push(item);
+            } else {
+                self.pending = Some((next, item));
+                break;
+            }
+        }
+
+        Some((key, group))
+    }
+}
+
 /// Extension trait for iterators that allows them to be chunked according to
 /// a classifier function.
 pub trait Chunkable
@@ -51,6 +97,13 @@ pub trait Chunkable
     fn chunk_by<K, C>(self, class: C) -> Chunked<Self, K, C>
         where K: Sized,
               C: FnMut(&<Self as Iterator>::Item) -> K;
+
+    /// Creates an iterator over runs of consecutive items with the same value
+    /// of `class`, yielding that value and the run's items.  Runs are only
+    /// read from the underlying iterator as they are needed.
+    fn group_by<K, C>(self, class: C) -> Groups<Self, K, C>
+        where K: PartialEq,
+              C: FnMut(&<Self as Iterator>::Item) -> K;
 }
 
 /// All iterators are chunkable.
@@ -61,6 +114,13 @@ impl <I> Chunkable for I where I: Iterator {
     {
         Chunked { prev: None, iter: self.peekable(), class }
     }
+
+    fn group_by<K, C>(self, class: C) -> Groups<I, K, C>
+        where K: PartialEq,
+              C: FnMut(&I::Item) -> K
+    {
+        Groups { iter: self, class, pending: None }
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +159,38 @@ mod tests {
         assert_eq!(3 + 4 + 5, s2);
         assert_eq!(6 + 7 + 8, s3);
     }
+
+    #[test]
+    fn groups() {
+        let words = ["apple", "avocado", "banana", "blueberry", "cherry", "apricot"];
+        let groups: Vec<(char, Vec<&&str>)> = words
+            .iter()
+            .group_by(|w| w.chars().next().unwrap())
+            .collect();
+
+        assert_eq!(groups, [
+            ('a', vec![&"apple", &"avocado"]),
+            ('b', vec![&"banana", &"blueberry"]),
+            ('c', vec![&"cherry"]),
+            ('a', vec![&"apricot"]),
+        ]);
+
+        assert_eq!((0..0).group_by(|n| *n).count(), 0);
+    }
+
+    #[test]
+    fn lazy_groups() {
+        let mut read = 0;
+        let firsts: Vec<(usize, usize)> = (0..)
+            .inspect(|_| read += 1)
+            .group_by(|n| n / 10)
+            .map(|(key, group)| (key, group[0]))
+            .take(2)
+            .collect();
+
+        assert_eq!(firsts, [(0, 0), (1, 10)]);
+
+        // The second group ends at the first item of the third.
+        assert_eq!(read, 21);
+    }
 }
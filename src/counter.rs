@@ -0,0 +1,163 @@
+//! Counting occurrences of values.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::FromIterator;
+
+/// A multiset: counts of how many times each value has been added.
+///
+/// Values are remembered in the order they were first added, and every
+/// method that lists them, including those that break ties between equal
+/// counts, does so in that order.
+#[derive(Clone, Debug)]
+pub struct Counter<T> {
+    /// Position of each value in `entries`.
+    index: HashMap<T, usize>,
+    entries: Vec<(T, usize)>,
+}
+
+impl <T> Counter<T> where T: Hash + Eq + Clone {
+    pub fn new() -> Counter<T> {
+        Counter { index: HashMap::new(), entries: vec![] }
+    }
+
+    /// Adds `item` once.
+    pub fn increment(&mut self, item: T) {
+        self.add(item, 1)
+    }
+
+    /// Adds `item` `n` times.
+    pub fn add(&mut self, item: T, n: usize) {
+        let Counter { index, entries } = self;
+        let ix = *index.entry(item.clone()).or_insert_with(|| {
+            entries.push((item, 0));
+            entries.len() - 1
+        });
+
+        entries[ix].1 += n;
+    }
+
+    /// Adds all of `other`'s counts to this counter's.  Values new to this
+    /// counter are remembered after its existing ones, in `other`'s order.
+    pub fn merge(&mut self, other: Counter<T>) {
+        for (item, n) in other.entries {
+            self.add(item, n);
+        }
+    }
+
+    /// The number of times `item` has been added.
+    pub fn get(&self, item: &T) -> usize {
+        self.index.get(item).map_or(0, |&ix| self.entries[ix].1)
+    }
+
+    /// Number of distinct values added.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of values added, counting repeats.
+    pub fn total(&self) -> usize {
+        self.entries.iter().map(|&(_, n)| n).sum()
+    }
+
+    /// Each value alongside its count, in the order they were first added.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.entries.iter().map(|(item, n)| (item, *n))
+    }
+
+    /// Each value alongside its count, from most to least common.
+    pub fn most_common(&self) -> Vec<(&T, usize)> {
+        let mut common: Vec<_> = self.iter().collect();
+        // Sorting is stable, so ties stay in the order they were added.
+        common.sort_by(|(_, a), (_, b)| b.cmp(a));
+        common
+    }
+
+    /// The `k` most common values alongside their counts, from most to least
+    /// common.
+    pub fn top(&self, k: usize) -> Vec<(&T, usize)> {
+        let mut common = self.most_common();
+        common.truncate(k);
+        common
+    }
+
+    /// The values with the highest count, which is the same for all of them,
+    /// or nothing if the counter is empty.
+    pub fn argmax(&self) -> (Vec<&T>, usize) {
+        let max = self.entries.iter().map(|&(_, n)| n).max().unwrap_or(0);
+        let items = self.iter().filter(|&(_, n)| n == max).map(|(item, _)| item).collect();
+        (items, max)
+    }
+}
+
+impl <T> Default for Counter<T> where T: Hash + Eq + Clone {
+    fn default() -> Counter<T> {
+        Counter::new()
+    }
+}
+
+impl <T> Extend<T> for Counter<T> where T: Hash + Eq + Clone {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, items: I) {
+        for item in items {
+            self.increment(item);
+        }
+    }
+}
+
+/// Counts each item of the iterator.
+impl <T> FromIterator<T> for Counter<T> where T: Hash + Eq + Clone {
+    fn from_iter<I: IntoIterator<Item = T>>(items: I) -> Counter<T> {
+        let mut counter = Counter::new();
+        counter.extend(items);
+        counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use counter::Counter;
+
+    #[test]
+    fn counts() {
+        let mut letters: Counter<char> = "mississippi".chars().collect();
+        assert_eq!(letters.get(&'s'), 4);
+        assert_eq!(letters.get(&'z'), 0);
+        assert_eq!((letters.len(), letters.total()), (4, 11));
+
+        letters.increment('m');
+        letters.add('z', 3);
+        assert_eq!(
+            letters.iter().collect::<Vec<_>>(),
+            [(&'m', 2), (&'i', 4), (&'s', 4), (&'p', 2), (&'z', 3)],
+        );
+
+        let mut more: Counter<char> = "zap".chars().collect();
+        more.merge(letters);
+        assert_eq!(
+            more.iter().collect::<Vec<_>>(),
+            [(&'z', 4), (&'a', 1), (&'p', 3), (&'m', 2), (&'i', 4), (&'s', 4)],
+        );
+    }
+
+    #[test]
+    fn ranking() {
+        let words: Counter<&str> = "b a c a b d c e".split(' ').collect();
+
+        assert_eq!(
+            words.most_common(),
+            [(&"b", 2), (&"a", 2), (&"c", 2), (&"d", 1), (&"e", 1)],
+        );
+
+        assert_eq!(words.top(2), [(&"b", 2), (&"a", 2)]);
+        assert_eq!(words.top(9).len(), 5);
+        assert_eq!(words.argmax(), (vec![&"b", &"a", &"c"], 2));
+
+        let empty: Counter<usize> = Counter::new();
+        assert_eq!(empty.argmax(), (vec![], 0));
+        assert!(empty.most_common().is_empty());
+    }
+}
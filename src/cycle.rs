@@ -0,0 +1,112 @@
+//! Detecting cycles in sequences of states, to skip ahead through
+//! simulations that would otherwise run for too many generations.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A repeating run of states: the state at generation `start + period` is the
+/// same as the one at `start`, and so on for every generation after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest generation whose state is the same as generation `n`'s.
+    pub fn equivalent(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// Number of whole periods that fit between generation `from`, which
+    /// must be part of the cycle, and generation `to`.  Skipping that many
+    /// periods from `from` comes as close to `to` as possible without passing
+    /// it.
+    pub fn skips(&self, from: usize, to: usize) -> usize {
+        assert!(from >= self.start, "Generation {} is before the cycle starts", from);
+        to.saturating_sub(from) / self.period
+    }
+}
+
+/// Remembers the states of a sequence, to spot the first one that repeats.
+pub struct CycleDetector<S> {
+    /// The generation each state was first seen at.
+    seen: HashMap<S, usize>,
+}
+
+impl <S> CycleDetector<S> where S: Eq + Hash {
+    pub fn new() -> CycleDetector<S> {
+        CycleDetector { seen: HashMap::new() }
+    }
+
+    /// Records `state` as the state at `generation`, returning the cycle it
+    /// completes if it has been seen before.  Generations need not be
+    /// consecutive, but must increase.
+    pub fn observe(&mut self, generation: usize, state: S) -> Option<Cycle> {
+        match self.seen.get(&state) {
+            Some(&start) => Some(Cycle { start, period: generation - start }),
+            None => {
+                self.seen.insert(state, generation);
+                None
+            },
+        }
+    }
+}
+
+impl <S> Default for CycleDetector<S> where S: Eq + Hash {
+    fn default() -> CycleDetector<S> {
+        CycleDetector::new()
+    }
+}
+
+/// The first cycle in `states`, which starts from generation 0, or `None` if
+/// it runs out before any state repeats.
+pub fn find_cycle<I>(states: I) -> Option<Cycle>
+    where I: IntoIterator,
+          I::Item: Eq + Hash
+{
+    let mut detector = CycleDetector::new();
+    states.into_iter()
+        .enumerate()
+        .filter_map(|(gen, state)| detector.observe(gen, state))
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use cycle::{find_cycle, Cycle, CycleDetector};
+    use std::iter;
+
+    #[test]
+    fn find() {
+        // 10, then 2, 6, 4, 5, 1, 3, 2, ...
+        let decimals = iter::successors(Some(10), |&n| Some(n * 10 % 7));
+        let cycle = find_cycle(decimals.clone()).unwrap();
+        assert_eq!(cycle, Cycle { start: 1, period: 6 });
+
+        let nth = |n| decimals.clone().nth(n).unwrap();
+        let far = 1_000_003;
+        assert_eq!(nth(cycle.equivalent(far)), nth(far));
+        assert_eq!(cycle.equivalent(0), 0);
+        assert_eq!(cycle.equivalent(7), 1);
+
+        assert_eq!(find_cycle(0 .. 10), None);
+    }
+
+    #[test]
+    fn skips() {
+        let mut detector = CycleDetector::new();
+        assert_eq!(detector.observe(0, "a"), None);
+        assert_eq!(detector.observe(10, "b"), None);
+        let cycle = detector.observe(30, "a").unwrap();
+
+        assert_eq!(cycle, Cycle { start: 0, period: 30 });
+        assert_eq!(cycle.skips(30, 100), 2);
+        assert_eq!(cycle.skips(30, 120), 3);
+        assert_eq!(cycle.skips(30, 20), 0);
+    }
+}
@@ -1,10 +1,11 @@
+extern crate lib;
+
+use lib::input;
 use std::collections::HashSet;
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 fn main() -> io::Result<()> {
-    let fname = env::args().nth(1).unwrap();
+    let input = input::load(1)?;
 
     let mut frequency: i32 = 0;
     let mut visited = HashSet::new();
@@ -12,10 +13,8 @@ fn main() -> io::Result<()> {
     visited.insert(frequency);
 
     loop {
-        let file = File::open(&fname)?;
-        let reader = BufReader::new(file);
-        for line in reader.lines() {
-            let delta: i32 = line.unwrap().parse().unwrap();
+        for line in input.lines() {
+            let delta: i32 = line.parse().unwrap();
             frequency += delta;
 
             if !visited.insert(frequency) {
@@ -1,25 +1,22 @@
+extern crate lib;
+
+use lib::counter::Counter;
+use lib::input;
 use std::collections::HashSet;
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 use std::vec::Vec;
 
 struct BoxID {
-    id: [u8; 26],
+    letters: Counter<char>,
 }
 
 impl BoxID {
     fn new(rep: &str) -> BoxID {
-        static LO: u8 = 'a' as u8;
-        let mut id = [0u8; 26];
-        for c in rep.chars() {
-           id[(c as u8 - LO) as usize] += 1
-        };
-        BoxID{id}
+        BoxID { letters: rep.chars().collect() }
     }
 
-    fn has_n(&self, n: u8) -> bool {
-        self.id.into_iter().find(|x| **x == n).is_some()
+    fn has_n(&self, n: usize) -> bool {
+        self.letters.iter().any(|(_, count)| count == n)
     }
 
     fn has_two(&self) -> bool {
@@ -49,17 +46,14 @@ fn similar_except_at(ix: usize, ids: &Vec<String>) -> Option<String> {
 }
 
 fn main() -> io::Result<()> {
-    let fname = env::args().nth(1).unwrap();
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
+    let input = input::load(2)?;
 
     let mut ids: Vec<String> = vec![];
 
     let mut twos = 0u32;
     let mut threes = 0u32;
-    for opt_line in reader.lines() {
-        let line = opt_line?;
-        let box_id = BoxID::new(&line);
+    for line in input.lines() {
+        let box_id = BoxID::new(line);
 
         if box_id.has_two() {
             twos += 1;
@@ -68,7 +62,7 @@ fn main() -> io::Result<()> {
             threes += 1;
         }
 
-        ids.push(line)
+        ids.push(line.to_owned())
     }
 
     println!("Checksum: {}", twos * threes);
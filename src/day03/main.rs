@@ -1,14 +1,12 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
 use std::collections::{BTreeSet, HashMap, HashSet};
 use std::collections::btree_set;
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use lib::input;
+use std::io;
 
 input! {
-    #["#{d} @ {d},{d}: {d}x{d}"; ""]
+    #["#{d} @ {d},{d}: {d}x{d}"; ""; Strict]
     struct Rect {
         id: usize,
         left: usize, top: usize,
@@ -53,17 +51,10 @@ impl Rect {
 
 type Input = HashMap<usize, Rect>;
 fn parse_input() -> io::Result<Input> {
-    let fname = env::args().nth(1).unwrap();
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
+    let input = input::load(3)?;
+    let rects = Rect::parse_all(input.reader()).in_file(&*input.name).all()?;
 
-    let mut rects = HashMap::new();
-    for line in reader.lines() {
-        let r = Rect::new(&line?)?;
-        rects.insert(r.id, r);
-    };
-
-    Ok(rects)
+    Ok(rects.into_iter().map(|r| (r.id, r)).collect())
 }
 
 struct ScanLine { boundaries: BTreeSet<Boundary> }
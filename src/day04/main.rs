@@ -1,35 +1,28 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
 use lib::chunkable::Chunkable;
-use std::collections::{HashMap, HashSet};
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use lib::counter::Counter;
+use lib::input;
+use std::collections::HashSet;
+use std::io;
 
 input! {
     enum Log {
-        #["[{d}-{d}-{d} {d}:{d}] Guard #{d}"; "begins shift"]
+        #["[{d}-{d}-{d} {d}:{d}] Guard #{d}"; " begins shift"]
         ShiftStart { y: usize, m: usize, d: usize, hr: usize, min: usize, id: usize },
 
-        #["[{d}-{d}-{d} {d}:{d}]"; "wakes up"]
+        #["[{d}-{d}-{d} {d}:{d}]"; " wakes up"]
         Wake { y: usize, m: usize, d: usize, hr: usize, min: usize },
 
-        #["[{d}-{d}-{d} {d}:{d}]"; "falls asleep"]
+        #["[{d}-{d}-{d} {d}:{d}]"; " falls asleep"]
         Sleep { y: usize, m: usize, d: usize, hr: usize, min: usize }
     }
 }
 
 type Input = Vec<Log>;
 fn parse_input() -> io::Result<Input> {
-    let fname = env::args().nth(1).unwrap();
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
-    let mut logs = vec![];
-
-    for line in reader.lines() {
-        logs.push(Log::new(&line?)?);
-    }
+    let input = input::load(4)?;
+    let mut logs = Log::parse_all(input.reader()).in_file(&*input.name).all()?;
 
     logs.sort_unstable_by_key(|log| match log {
         Log::ShiftStart{ y, m, d, hr, min, ..}
@@ -70,7 +63,7 @@ fn sleepiest_minute(guard: usize, events: &Vec<Event>) -> (usize, usize) {
         .collect();
 
     guard_events.sort_unstable_by_key(|event| event.min);
-    let mut days_by_min = guard_events
+    let days_by_min = guard_events
         .iter()
         .scan(0, |days, event| {
             let Event { desc, .. } = event;
@@ -81,12 +74,13 @@ fn sleepiest_minute(guard: usize, events: &Vec<Event>) -> (usize, usize) {
 
             Some((*days, event.min))
         })
-        .chunk_by(|(_, min)| *min)
-        .peekable();
+        .group_by(|&(_, min)| min);
 
     let mut max_min = 0;
     let mut max_days = 0;
-    while let Some((days, min)) = (&mut days_by_min).last() {
+    for (min, counts) in days_by_min {
+        // Only the count after the last event at each minute matters.
+        let &(days, _) = counts.last().expect("Groups are never empty");
         if days > max_days {
             max_min = min;
             max_days = days;
@@ -135,17 +129,14 @@ fn main() -> io::Result<()> {
             }
         }).filter_map(|v| v);
 
-    let mut sleep_totals = HashMap::new();
+    let mut sleep_totals = Counter::new();
     for SleepRange { start, end, guard } in sleep_durations {
-        let mut counter = sleep_totals.entry(guard).or_insert(0);
-        *counter += end - start;
+        sleep_totals.add(guard, end - start);
     }
 
     {
-        let (&sleepiest, _) = sleep_totals
-            .iter()
-            .max_by_key(|(_, total)| *total)
-            .unwrap();
+        let (sleepiest, _) = sleep_totals.most_common()[0];
+        let sleepiest = *sleepiest;
 
         let (min, count) = sleepiest_minute(sleepiest, &sleep_events);
 
@@ -1,6 +1,7 @@
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+extern crate lib;
+
+use lib::input;
+use std::io;
 use std::str;
 use std::string::ToString;
 
@@ -90,12 +91,7 @@ fn react(mut poly: Polymer) -> usize {
 }
 
 fn main() -> io::Result<()> {
-    let fname = env::args().nth(1).unwrap();
-
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
-
-    let input = reader.lines().next().unwrap()?;
+    let input = input::load(5)?.first_line().to_owned();
 
     {
         let polymer = Polymer::new(input.clone());
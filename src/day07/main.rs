@@ -1,14 +1,12 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
 use std::cmp::{Ord, PartialOrd, Ordering};
 use std::collections::{BinaryHeap, BTreeMap, BTreeSet};
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use lib::input;
+use std::io;
 
 input! {
-    #["Step {} must be finished before step {}"; "can begin."]
+    #["Step {} must be finished before step {}"; " can begin."]
     struct Dep {
         before: char,
         after: char
@@ -109,14 +107,8 @@ fn cost(id: char) -> usize {
 }
 
 fn main() -> io::Result<()> {
-    let fname = env::args().nth(1).unwrap();
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
-
-    let deps = reader
-        .lines()
-        .map(|l| Dep::new(&l?))
-        .collect::<io::Result<Vec<_>>>()?;
+    let input = input::load(7)?;
+    let deps = Dep::parse_all(input.reader()).in_file(&*input.name).all()?;
 
     {
         let mut graph = Graph::new();
@@ -1,6 +1,14 @@
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+#[macro_use] extern crate lib;
+
+use lib::input;
+use std::io;
+
+input! {
+    #["{d*}"; ""]
+    struct Tree {
+        nums: Vec<usize>
+    }
+}
 
 fn sum_metadata<I: Iterator<Item = usize>>(iter: &mut I) -> Option<usize> {
     let children = iter.next()?;
@@ -47,17 +55,9 @@ fn value<I: Iterator<Item = usize>>(iter: &mut I) -> Option<usize> {
 
 
 fn main() -> io::Result<()> {
-    let fname = env::args().nth(1).unwrap();
-    let file = File::open(fname)?;
-    let mut reader = BufReader::new(file);
-
-    let mut input = String::new();
-    reader.read_line(&mut input)?;
-
-    let nums = input.split_whitespace()
-        .map(|token| token.parse::<usize>())
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap();
+    let input = input::load(8)?;
+    let Tree { nums } = Tree::new(input.first_line())
+        .map_err(|e| e.in_file(&*input.name).on_line(1))?;
 
     println!("Part 1: {:?}", sum_metadata(&mut nums.iter().cloned()));
     println!("Part 2: {:?}", value(&mut nums.iter().cloned()));
@@ -1,10 +1,8 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
-use std::env;
+use lib::input;
 use std::fmt;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io;
 
 input! {
     #["{d} players; last marble is worth {d}"; " points"]
@@ -161,13 +159,9 @@ impl <T> fmt::Debug for LinkedList<T>
 
 fn main() -> io::Result<()> {
     let params = {
-        let fname = env::args().nth(1).unwrap();
-        let file = File::open(fname)?;
-        let mut reader = BufReader::new(file);
-        let mut line = String::new();
-        reader.read_line(&mut line)?;
-
-        GameParams::new(line.trim())?
+        let input = input::load(9)?;
+        GameParams::new(input.first_line())
+            .map_err(|e| e.in_file(&*input.name).on_line(1))?
     };
 
     let mut game = Game::new();
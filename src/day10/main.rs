@@ -1,11 +1,11 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
+use lib::grid::Grid;
+use lib::input;
 use std::env;
 use std::fmt;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
-use std::str;
+use std::io;
+use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
 
@@ -27,26 +27,22 @@ impl Point {
 }
 
 struct Canvas {
-    width: usize,
-    height: usize,
-    buffer: String,
+    pixels: Grid<char>,
+    /// Directory to save each frame to, as an image.
+    frames: Option<PathBuf>,
 }
 
 impl Canvas {
-    fn new(width: usize, height: usize) -> Canvas {
-        // width + 1 to accommodate new-lines.
-        let mut buffer = String::with_capacity(height * (width + 1));
-        let line = {
-            let mut prefix = str::repeat(" ", width);
-            prefix.push('\n');
-            prefix
-        };
+    fn new(width: usize, height: usize, frames: Option<PathBuf>) -> Canvas {
+        Canvas { pixels: Grid::new(width, height, ' '), frames }
+    }
 
-        for _ in 0 .. height {
-            buffer.push_str(&line);
-        }
+    fn width(&self) -> usize {
+        self.pixels.width()
+    }
 
-        Canvas { width, height, buffer }
+    fn height(&self) -> usize {
+        self.pixels.height()
     }
 
     fn clear_screen() {
@@ -54,25 +50,27 @@ impl Canvas {
     }
 
     fn paint(&mut self, x: usize, y: usize) {
-        let i = (self.width + 1) * y + x;
-        unsafe { self.buffer.as_mut_vec()[i] = '#' as u8; }
+        self.pixels[(x, y)] = '#';
     }
 
-    fn flush(&mut self) {
+    fn flush(&mut self, t: isize) -> io::Result<()> {
         println!("{}", self);
-        unsafe {
-            for px in self.buffer.as_mut_vec().iter_mut() {
-                if *px != '\n' as u8 {
-                    *px = ' ' as u8;
-                }
-            }
+
+        if let Some(dir) = &self.frames {
+            self.pixels
+                .pbm(|&px| px == '#')
+                .scale(4)
+                .save(dir.join(format!("frame-{}.pbm", t)))?;
         }
+
+        self.pixels = Grid::new(self.width(), self.height(), ' ');
+        Ok(())
     }
 }
 
 impl fmt::Display for Canvas {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.buffer)
+        write!(f, "{}", self.pixels)
     }
 }
 
@@ -85,7 +83,7 @@ impl PointCloud {
         PointCloud { constituents }
     }
 
-    fn display_at_time(&self, t: isize, canvas: &mut Canvas) -> (isize, bool) {
+    fn display_at_time(&self, t: isize, canvas: &mut Canvas) -> io::Result<(isize, bool)> {
         let posns: Vec<(isize, isize)> =
             self.constituents.iter().map(|p| p.at_time(t)).collect();
 
@@ -109,11 +107,11 @@ impl PointCloud {
             0.max(dim - 1) / sf as isize + 1
         }
 
-        let res = scale(width, canvas.width)
-            .max(scale(height, canvas.height));
+        let res = scale(width, canvas.width())
+            .max(scale(height, canvas.height()));
 
         if res > 1 {
-            return (res, false)
+            return Ok((res, false))
         }
 
         for &(x, y) in &posns {
@@ -126,29 +124,24 @@ impl PointCloud {
         Canvas::clear_screen();
         println!("t = {: <5}, res = {: <5}, origin = ({}, {})", t, res, xlo, ylo);
 
-        canvas.flush();
-        (res, true)
+        canvas.flush(t)?;
+        Ok((res, true))
     }
 }
 
 fn main() -> io::Result<()> {
     let points = {
-        let fname = env::args().nth(1).unwrap();
-        let file = File::open(fname)?;
-        let reader = BufReader::new(file);
-
-        PointCloud::new(
-            reader.lines()
-                .map(|l| Point::new(&l?))
-                .collect::<io::Result<Vec<_>>>()?
-        )
+        let input = input::load(10)?;
+        PointCloud::new(Point::parse_all(input.reader()).in_file(&*input.name).all()?)
     };
 
     static TICK: Duration = Duration::from_millis(1000);
     let mut time = 0;
-    let mut canvas = Canvas::new(80, 60);
+    // Frames are also saved to the directory named by the second argument,
+    // if there is one.
+    let mut canvas = Canvas::new(80, 60, env::args().nth(2).map(PathBuf::from));
     loop {
-        let (res, did_draw) = points.display_at_time(time, &mut canvas);
+        let (res, did_draw) = points.display_at_time(time, &mut canvas)?;
 
         time += if res < 10 {
             1
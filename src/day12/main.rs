@@ -1,10 +1,9 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
-use std::collections::HashMap;
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use lib::cycle::CycleDetector;
+use lib::grid::Grid;
+use lib::input;
+use std::io;
 use std::iter::Iterator;
 
 input! {
@@ -164,14 +163,7 @@ struct Pots {
     liveness: BitVec,
 }
 
-#[derive(Debug)]
-struct Config {
-    offset: isize,
-    generation: usize,
-}
-
 type TrnMap = [bool; 32];
-type CfgMap = HashMap<Vec<bool>, Config>;
 
 static PAD: usize = 2;
 
@@ -225,13 +217,9 @@ impl Pots {
         }
     }
 
-    fn memoize(&self, generation: usize, configs: &mut CfgMap) -> Option<Config> {
-        let &Pots { offset, .. } = self;
-
-        let patt: Vec<_> = self.liveness.iter().collect();
-        let config = Config { offset, generation };
-
-        configs.insert(patt, config)
+    /// Which pots are live, relative to the offset.
+    fn pattern(&self) -> Vec<bool> {
+        self.liveness.iter().collect()
     }
 
     fn live_pot_sum(&self) -> isize {
@@ -247,29 +235,16 @@ impl Pots {
     }
 
     fn print_range(&self, from: isize, to: isize) {
-        let mut i = from;
-
-        let before = to.min(self.offset);
-        while i < before {
-            print!(".");
-            i += 1
-        }
-
-        let after = to.min(self.offset + self.liveness.len() as isize);
-        while i < after {
-            let ix = (i - self.offset) as usize;
-            let is_live = self.liveness.get(ix) == 1;
-            let chr = if is_live { '#' } else { '.' };
-            print!("{}", chr);
-            i += 1;
-        }
-
-        while i < to {
-            print!(".");
-            i += 1;
-        }
+        let width = 0.max(to - from) as usize;
+        let row = Grid::new_with_mapping(width, 1, |i, _| {
+            let ix = from + i as isize - self.offset;
+            0 <= ix && (ix as usize) < self.liveness.len()
+                && self.liveness.get(ix as usize) == 1
+        });
 
-        println!(" [{}, +{}]", self.offset, self.liveness.len());
+        print!("{}", row.render().annotate(|_| {
+            format!("[{}, +{}]", self.offset, self.liveness.len())
+        }));
     }
 
     fn print(&self) {
@@ -281,23 +256,21 @@ impl Pots {
 
 fn main() -> io::Result<()> {
     let (init, trn) = {
-        let fname = env::args().nth(1).unwrap();
-        let file = File::open(fname)?;
-        let mut reader = BufReader::new(file);
+        let input = input::load(12)?;
+        let mut lines = input.lines();
 
-        let mut buf = String::new();
-        reader.read_line(&mut buf)?;
-        let init_parsed = InitialState::new(buf.trim())?;
+        let init_parsed = InitialState::new(lines.next().unwrap_or(""))
+            .map_err(|e| e.in_file(&*input.name).on_line(1))?;
 
-        buf.clear();
-        reader.read_line(&mut buf)?;
         assert_eq!(
-            buf.trim().len(), 0,
+            lines.next(), Some(""),
             "Expected empty line between initial state and transition map");
 
         let mut trn = [false; 32];
-        for line in reader.lines() {
-            let entry = Transition::new(&line?)?;
+        // The transitions start on the third line.
+        for (i, line) in lines.enumerate() {
+            let entry = Transition::new(line)
+                .map_err(|e| e.in_file(&*input.name).on_line(i + 3))?;
             entry.update(&mut trn);
         }
 
@@ -309,32 +282,40 @@ fn main() -> io::Result<()> {
     let mut pots = Pots::new(&init);
     let gens: usize = 50_000_000_000;
 
-    let mut prev_configs = HashMap::new();
+    // The same pattern of live pots may recur at a different offset: Find
+    // the first time this happens, and skip ahead, moving the pots by the
+    // same amount for each cycle skipped.
+    let mut history = CycleDetector::new();
+    let mut offsets = vec![];
+    let mut cycle = None;
 
     let mut i = 0;
-    while i < gens {
-        pots.next(&trn);
-        i += 1;
+    while i < gens && cycle.is_none() {
+        pots.normalize();
+        offsets.push(pots.offset);
+        cycle = history.observe(i, pots.pattern());
 
-        if i % 97 == 0 {
-            pots.normalize();
-            if let Some(prev) = pots.memoize(i, &mut prev_configs) {
-                // We have seen this configuration before.  Use this fact to fast
-                // forward the simulation.
+        if cycle.is_none() {
+            pots.next(&trn);
+            i += 1;
+        }
+    }
 
-                let time_left = gens - i;
-                let time_delta = i - prev.generation;
-                let skips = time_left / time_delta;
-                let drift = pots.offset - prev.offset;
+    if let Some(cycle) = cycle {
+        let skips = cycle.skips(i, gens);
+        let drift = pots.offset - offsets[cycle.start];
 
-                println!("Drift detected: {} -> {} by {}", prev.generation, i, drift);
-                pots.print();
+        println!("Drift detected: {} -> {} by {}", cycle.start, i, drift);
+        pots.print();
 
-                pots.travel(drift * skips as isize);
-                i += time_delta * skips;
-                println!("Fast forwarded to {}", i);
-            }
-        }
+        pots.travel(drift * skips as isize);
+        i += cycle.period * skips;
+        println!("Fast forwarded to {}", i);
+    }
+
+    while i < gens {
+        pots.next(&trn);
+        i += 1;
     }
 
     println!("Live: {}", pots.live_pot_sum());
@@ -5,9 +5,8 @@ extern crate num_traits;
 use lib::grid::Grid;
 use num_traits::cast::FromPrimitive;
 use std::collections::BTreeMap;
-use std::env;
-use std::fs::File;
-use std::io::{self, Read, BufRead, BufReader};
+use lib::input;
+use std::io;
 
 #[derive(Copy, Clone, Debug)]
 enum CornerType {
@@ -32,8 +31,8 @@ impl CornerType {
     }
 }
 
-#[derive(Copy, Clone)]
-enum Cell { Empty, Horiz, Vert, XSect, Corner(CornerType) }
+#[derive(Copy, Clone, Default)]
+enum Cell { #[default] Empty, Horiz, Vert, XSect, Corner(CornerType) }
 
 #[derive(Copy, Clone, Debug)]
 enum Turn { Straight = 0, Right = 1, Left = 3 }
@@ -77,16 +76,19 @@ impl Dir {
         Dir::from_u8(e).unwrap()
     }
 
-    fn travel_from(self, pos: (usize, usize)) -> (usize, usize) {
+    /// The position one step from `pos` in this direction, if it is still
+    /// on `grid`.
+    fn travel_from<T>(self, pos: (usize, usize), grid: &Grid<T>) -> Option<(usize, usize)> {
         use Dir::*;
 
-        let (i, j) = pos;
-        match self {
-            Up    => (i, j - 1),
-            Right => (i + 1, j),
-            Down  => (i, j + 1),
-            Left  => (i - 1, j),
-        }
+        let delta = match self {
+            Up    => ( 0, -1),
+            Right => ( 1,  0),
+            Down  => ( 0,  1),
+            Left  => (-1,  0),
+        };
+
+        grid.step(pos, delta)
     }
 }
 
@@ -102,18 +104,18 @@ impl Cart {
 }
 
 impl Cell {
-    fn from_byte(b: u8) -> Cell {
+    fn from_byte(b: u8) -> Option<Cell> {
         use Cell::*;
 
         match b as char {
-            ' '  => Empty,
-            '+'  => XSect,
-            '/'  => Corner(CornerType::ULDR),
-            '\\' => Corner(CornerType::URDL),
-
-            '-' | '<' | '>' => Cell::Horiz,
-            '|' | '^' | 'v' => Cell::Vert,
-            _ => panic!("Unexpected character"),
+            ' '  => Some(Empty),
+            '+'  => Some(XSect),
+            '/'  => Some(Corner(CornerType::ULDR)),
+            '\\' => Some(Corner(CornerType::URDL)),
+
+            '-' | '<' | '>' => Some(Horiz),
+            '|' | '^' | 'v' => Some(Vert),
+            _ => None,
         }
     }
 
@@ -135,36 +137,16 @@ struct Tracks(Grid<Cell>);
 type Carts = BTreeMap<(usize, usize), Cart>;
 
 impl Tracks {
-    fn new(init: String) -> io::Result<(Tracks, Carts)> {
-        let (width, height) = {
-            let mut r = BufReader::new(init.as_bytes());
-            let mut w = 0;
-            let mut h = 0;
-
-            for l in r.lines() {
-                h += 1;
-                w = w.max(l?.len());
-            }
-
-            (w, h)
-        };
-
-        let mut map = Grid::new(width, height, Cell::Empty);
+    fn new(init: &str) -> io::Result<(Tracks, Carts)> {
         let mut carts = Carts::new();
-
-        {
-            let r = BufReader::new(init.as_bytes());
-            for (j, l) in r.lines().enumerate() {
-                for (i, b) in l?.as_bytes().iter().enumerate() {
-                    map[(i, j)] = Cell::from_byte(*b);
-
-                    if let Some(c) = Cart::from_byte(*b) {
-                        // Y-coord first to order top-bottom, left-right.
-                        carts.insert((j, i), c);
-                    }
-                }
+        let map = Grid::parse(init, |i, j, b| {
+            if let Some(c) = Cart::from_byte(b) {
+                // Y-coord first to order top-bottom, left-right.
+                carts.insert((j, i), c);
             }
-        }
+
+            Cell::from_byte(b)
+        })?;
 
         Ok((Tracks(map), carts))
     }
@@ -178,7 +160,8 @@ impl Tracks {
             // It might not exist because it could have been crashed into.
             if let Some(mut cart) = posns.remove(&(j, i)) {
                 // Move in direction cart is facing
-                let (k, l) = cart.dir.travel_from((i, j));
+                let (k, l) = cart.dir.travel_from((i, j), grid)
+                    .unwrap_or_else(|| panic!("Cart at {},{} ran off the map", i, j));
 
                 // Find new cell under cart
                 let cell = grid[(k, l)];
@@ -200,12 +183,7 @@ impl Tracks {
 
 fn main() -> io::Result<()> {
     let (tracks, mut carts) = {
-        let fname = env::args().nth(1).unwrap();
-        let mut file = File::open(fname)?;
-        let mut buf = String::new();
-
-        file.read_to_string(&mut buf)?;
-        Tracks::new(buf)?
+        Tracks::new(input::load(13)?.text())?
     };
 
     println!("#Carts = {}", carts.len());
@@ -254,4 +232,14 @@ mod test {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn travel_from_edges() {
+        let grid = Grid::new(2, 2, Cell::Empty);
+        assert_eq!(Dir::Up.travel_from((1, 1), &grid), Some((1, 0)));
+        assert_eq!(Dir::Up.travel_from((1, 0), &grid), None);
+        assert_eq!(Dir::Left.travel_from((0, 1), &grid), None);
+        assert_eq!(Dir::Right.travel_from((1, 1), &grid), None);
+        assert_eq!(Dir::Down.travel_from((0, 0), &grid), Some((0, 1)));
+    }
 }
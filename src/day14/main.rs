@@ -1,5 +1,8 @@
-use std::collections::VecDeque;
-use std::env;
+#[macro_use] extern crate lib;
+
+use lib::input;
+use lib::sliding::Sliding;
+use std::io;
 
 static TAIL: usize = 10;
 
@@ -51,8 +54,18 @@ impl Iterator for Recipes {
     }
 }
 
-fn main() {
-    let count_str = env::args().nth(1).unwrap();
+input! {
+    #["{[0-9]}"; ""; Lenient]
+    struct Digits { digits: String }
+}
+
+fn main() -> io::Result<()> {
+    let Digits { digits: count_str } = {
+        let input = input::load_inline(14)?;
+        Digits::new(input.first_line())
+            .map_err(|e| e.in_file(&*input.name).on_line(1))?
+    };
+
     let count: usize = count_str.parse().unwrap();
 
     {
@@ -65,26 +78,10 @@ fn main() {
     }
 
     {
-        let sub_seq: VecDeque<u8> = count_str.chars()
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect();
-
-        let mut recipes = Recipes::new();
-        let mut window: VecDeque<_> = (&mut recipes).take(sub_seq.len())
-            .collect();
-
-        let mut i = 0;
-        loop {
-            if sub_seq == window {
-                break;
-            }
-
-            window.pop_front();
-            window.push_back(recipes.next().unwrap());
-            i += 1
-        }
-
+        let sub_seq = count_str.chars().map(|c| c.to_digit(10).unwrap() as u8);
+        let i = Recipes::new().subsequence_offsets(sub_seq).next().unwrap();
         println!("Part 2: {}", i);
-
     }
+
+    Ok(())
 }
@@ -1,12 +1,13 @@
 extern crate lib;
 extern crate termion;
 
-use lib::grid::Grid;
-use std::collections::{BTreeSet, HashMap, VecDeque};
-use std::env;
+use lib::counter::Counter;
+use lib::grid::{CellChar, Grid};
+use std::collections::HashMap;
+use lib::input;
+use lib::search::{reading_order, Bfs};
 use std::fmt;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
+use std::io;
 use std::thread;
 use std::time::Duration;
 use termion::clear;
@@ -41,19 +42,23 @@ impl Class {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Cell { Empty, Wall, Entity(usize) }
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+enum Cell { #[default] Empty, Wall, Entity(usize) }
 impl Cell {
-    fn from_byte(b: u8) -> Cell {
+    /// Parses cells other than entities, which are parsed by `Class`.
+    fn from_byte(b: u8) -> Option<Cell> {
         use Cell::*;
         match b as char {
-            '.' => Empty,
-            '#' => Wall,
-            _   => Entity(0),
+            '.' => Some(Empty),
+            '#' => Some(Wall),
+            _   => None,
         }
     }
+}
 
-    fn to_char(&self) -> char {
+/// Entities are drawn as `X`: `Game` draws their class over them.
+impl CellChar for Cell {
+    fn cell_char(&self) -> char {
         use Cell::*;
         match self {
             Empty => '.',
@@ -84,24 +89,9 @@ enum Step {
     Towards(usize, usize),
 }
 
-#[derive(Clone, Copy)]
-enum Search {
-    Unexplored,
-    Explored(usize),
-}
-
-impl fmt::Debug for Search {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Search::Unexplored => write!(f, "."),
-            Search::Explored(d) => write!(f, "{}", d),
-        }
-    }
-}
-
 enum SimResult {
     Win(Class),
-    Ongoing(HashMap<Class, usize>),
+    Ongoing(Counter<Class>),
 }
 
 struct Game {
@@ -110,40 +100,16 @@ struct Game {
 }
 
 impl Game {
-    fn new(init: String, aps: HashMap<Class, usize>) -> io::Result<Game> {
-        let (width, height) = {
-            let mut r = BufReader::new(init.as_bytes());
-            let mut w = 0;
-            let mut h = 0;
-
-            for l in r.lines() {
-                h += 1;
-                w = w.max(l?.len());
-            }
-
-            (w, h)
-        };
-
-        let mut map = Grid::new(width, height, Cell::Empty);
+    fn new(init: &str, aps: HashMap<Class, usize>) -> io::Result<Game> {
         let mut entities = Vec::new();
+        let map = Grid::parse(init, |i, j, b| match Class::from_byte(b) {
+            Some(c) => {
+                entities.push(Entity::new(c, aps[&c], i, j));
+                Some(Cell::Entity(entities.len() - 1))
+            },
 
-        {
-            let r = BufReader::new(init.as_bytes());
-            for (j, l) in r.lines().enumerate() {
-                for (i, b) in l?.as_bytes().iter().enumerate() {
-                    map[(i, j)] = Cell::from_byte(*b);
-
-                    if let Cell::Entity(ref mut id) = map[(i, j)] {
-                        *id = entities.len();
-                        entities.push(
-                            Class::from_byte(*b)
-                                .map(|c| Entity::new(c, aps[&c], i, j))
-                                .expect("Entity from class"));
-                    }
-                }
-            }
-        }
-
+            None => Cell::from_byte(b),
+        })?;
 
         Ok(Game { map, entities })
     }
@@ -161,86 +127,33 @@ impl Game {
     fn find_nearest(&self, i: usize, j: usize, class: Class) -> Step {
         let Game { entities, map } = self;
 
-        struct Frontier {
-            x: usize,
-            y: usize,
-            dist: usize,
-        }
-
-        let mut frontier = VecDeque::new();
-        frontier.push_back(Frontier { x: i, y: j, dist: 0 });
-
-        let mut frontier: VecDeque<_> = neighbours(i, j)
-            .iter().cloned()
-            .map(|(x, y)| Frontier { x, y, dist: 1 })
-            .collect();
-
-        let mut search =
-            Grid::new(map.width(), map.height(), Search::Unexplored);
-
-        // Initialise the starting point as already explored.
-        search[(i, j)] = Search::Explored(0);
-
-        let mut nearest = None;
-        let mut candidates = vec![];
-        while let Some(Frontier { x, y, dist }) = frontier.pop_front() {
-            if let Some(ndist) = nearest {
-                if ndist < dist {
-                    // We found a nearer candidate, everything in the frontier
-                    // after this will only be further away.
-                    break;
-                }
-            }
-
-            // If we have already explored this region, we need not do it
-            // again.
-            if let Search::Explored(sdist) = search[(x, y)] {
-                if sdist > dist {
-                    panic!("Ordering inversion");
-                } else {
-                    continue;
-                }
-            }
-
-            match map[(x, y)] {
-                // Walls stem the search.
-                Cell::Wall => continue,
-
-                // Empty cells warrant further exploration
-                Cell::Empty => {
-                    for &(ni, nj) in neighbours(x, y).iter() {
-                        frontier.push_back(
-                            Frontier { x: ni, y: nj, dist: dist + 1 }
-                        );
-                    }
-                },
+        let is_target = |&pos: &(usize, usize)| match map[pos] {
+            Cell::Entity(id) => entities[id].class == class,
+            _ => false,
+        };
 
-                Cell::Entity(id) => {
-                    let Entity { class: c, .. } = entities[id];
-                    if c != class {
-                        // Entities of an incorrect class also stem the search.
-                        continue
-                    } else {
-                        // Entities of the correct class are potential
-                        // candidates.
-                        let ndist = *nearest.get_or_insert(dist);
-                        assert!(ndist == dist);
-
-                        // Push with y co-ordinate first, to make it easy to
-                        // search in "reading" order.
-                        candidates.push((y, x));
-                    }
-                },
-            };
+        // Search through empty cells, stopping at entities of the target
+        // class: Walls and other entities stem the search.
+        let mut search = Bfs::new((i, j), |pos| {
+            let expand = pos == (i, j) || map[pos] == Cell::Empty;
+            map.neighbours4(pos)
+                .filter(move |&n| expand && (map[n] == Cell::Empty || is_target(&n)))
+        });
+
+        let target = match search.nearest(is_target, reading_order) {
+            Some(target) => target,
+            None => return Step::NotFound,
+        };
 
-            search[(x, y)] = Search::Explored(dist);
+        if search.dist(&target) == Some(1) {
+            return Step::Adjacent;
         }
 
-        candidates.sort_unstable();
-        candidates.first().map_or(
-            Step::NotFound,
-            |&(cj, ci)| chart_course(i, j, ci, cj, &search, &map),
-        )
+        let path = search.path_to(target, reading_order)
+            .expect("Path to nearest target");
+
+        let (k, l) = path[1];
+        Step::Towards(k, l)
     }
 
     /// Returns true if the attack caused a kill.
@@ -255,8 +168,8 @@ impl Game {
         };
 
         let enemy = class.enemy();
-        let weakest = neighbours(i, j).iter()
-            .filter_map(|&(k, l)| {
+        let weakest = map.neighbours4((i, j))
+            .filter_map(|(k, l)| {
                 if let Cell::Entity(id) = map[(k, l)] {
                     let e = &entities[id];
                     if e.class == enemy {
@@ -268,7 +181,7 @@ impl Game {
             }).min();
 
         if let Some(low_hp) = weakest {
-            for &(k, l) in &neighbours(i, j) {
+            for (k, l) in map.neighbours4((i, j)) {
                 if let Cell::Entity(id) = map[(k, l)] {
                     let e = &mut entities[id];
                     if e.class == enemy && e.hp == low_hp {
@@ -320,7 +233,7 @@ impl Game {
             coords
         };
 
-        let mut deaths = HashMap::new();
+        let mut deaths = Counter::new();
         for (j, i) in play_order {
             // Check there are some entities of each type.
             for c in &[Class::Goblin, Class::Elf] {
@@ -348,8 +261,7 @@ impl Game {
                     };
 
                     if self.try_attack(k, l) {
-                        let mut count = deaths.entry(enemy).or_insert(0);
-                        *count += 1;
+                        deaths.increment(enemy);
                     }
                 }
             }
@@ -362,108 +274,28 @@ impl Game {
 impl fmt::Debug for Game {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let Game { map, entities } = self;
-        for j in 0 .. map.height() {
-            let mut health = vec![];
-            for i in 0 .. map.width() {
-                let cell = map[(i, j)];
-                if let Cell::Entity(id) = cell {
-                    let Entity { class, hp, .. } = entities[id];
-                    write!(f, "{}", class.to_char())?;
-                    health.push((class, hp));
-                } else {
-                    write!(f, "{}", cell.to_char())?;
-                }
-            }
-
-            writeln!(
-                f, " {}",
-                health.iter()
-                    .map(|(c, h)| format!("{}({})", c.to_char(), h))
+        let render = map.render()
+            .overlay(|_, _, cell| match *cell {
+                Cell::Entity(id) => Some(entities[id].class.to_char()),
+                _ => None,
+            })
+            .annotate(|j| {
+                (0 .. map.width())
+                    .filter_map(|i| match map[(i, j)] {
+                        Cell::Entity(id) => Some(&entities[id]),
+                        _ => None,
+                    })
+                    .map(|e| format!("{}({})", e.class.to_char(), e.hp))
                     .collect::<Vec<_>>()
-                    .join(", "),
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-/// Neighbours to point (i, j), in "reading order" (top to bottom, left to
-/// right).  Assumes all neighbours exist -- this assumption works because the
-/// inputs all have bounding walls, which we do not take the neighbours of.
-fn neighbours(i: usize, j: usize) -> [(usize, usize); 4] {
-    [
-        (i, j - 1),
-        (i - 1, j),
-        (i + 1, j),
-        (i, j + 1),
-    ]
-}
-
-fn distance(i: usize, j: usize, k: usize, l: usize) -> usize {
-    let si = i as isize;
-    let sj = j as isize;
-    let sk = k as isize;
-    let sl = l as isize;
+                    .join(", ")
+            });
 
-    ((sk - si).abs() + (sl - sj).abs()) as usize
-}
-
-fn chart_course(
-    i: usize, j: usize, ci: usize, cj: usize, search: &Grid<Search>, map: &Grid<Cell>,
-) -> Step {
-    // println!("({}, {}) -> ({}, {})", i, j, ci, cj);
-    // println!("{:?}", search);
-
-    if distance(i, j, ci, cj) == 1 {
-        // println!("Adjacent");
-        return Step::Adjacent;
-    }
-
-    let mut frontier = VecDeque::new();
-    let mut candidates = BTreeSet::new();
-
-    frontier.push_back((ci, cj));
-
-    while let Some((k, l)) = frontier.pop_front() {
-        let s = search[(k, l)];
-        match s {
-            Search::Unexplored => {
-                let err = format!("chart_course: Unexplored @ ({}, {})", k, l);
-                panic!(err)
-            },
-
-            Search::Explored(0) => panic!("chart_course: Self"),
-
-            // Inverted co-ords for reading-order sorting purposes.
-            Search::Explored(1) => { candidates.insert((l, k)); },
-
-            // Further away, expand the frontier.
-            Search::Explored(d) => for &(ni, nj) in neighbours(k, l).iter() {
-                if let Cell::Empty = map[(ni, nj)] {
-                    if let Search::Explored(e) = search[(ni, nj)] {
-                        if e == d - 1 {
-                            // This neighbour is one step nearer to the target,
-                            // explore it as an extension of the path.
-                            frontier.push_back((ni, nj));
-                        }
-                    }
-                }
-            }
-        }
+        write!(f, "{}", render)
     }
-
-    // println!("Step: {:?}", candidates);
-    candidates.iter().next().map_or(Step::NotFound, |&(l, k)| Step::Towards(k, l))
 }
 
 fn parse_input(aps: HashMap<Class, usize>) -> io::Result<Game> {
-    let fname = env::args().nth(1).unwrap();
-    let mut file = File::open(fname)?;
-    let mut buf = String::new();
-    file.read_to_string(&mut buf)?;
-
-    Game::new(buf, aps)
+    Game::new(input::load(15)?.text(), aps)
 }
 
 fn part1() -> io::Result<usize> {
@@ -527,7 +359,7 @@ fn try_elf_ap(eap: usize, print: bool) -> io::Result<Option<usize>> {
             },
 
             SimResult::Ongoing(deaths) =>
-                if deaths.get(&Class::Elf).cloned().unwrap_or(0) > 0 {
+                if deaths.get(&Class::Elf) > 0 {
                     if print { println!("[-] Elf Death") }
                     return Ok(None)
                 } else {
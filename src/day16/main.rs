@@ -1,11 +1,9 @@
 #[macro_use] extern crate lib;
-#[macro_use] extern crate scan_fmt;
 
+use lib::input::{self, Source};
+use lib::records::ReadRecords;
 use std::collections::{HashMap, HashSet};
-use std::env;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader};
-use std::iter::Peekable;
+use std::io::{self, BufRead};
 
 type RegisterFile = [usize; 4];
 type Translation = [OpCode; 16];
@@ -90,62 +88,11 @@ input! {
     }
 }
 
-struct Sample {
-    before: Before,
-    insn:   Insn,
-    after:  After,
-}
-
-impl Sample {
-    fn new(
-            b: io::Result<String>,
-            i: io::Result<String>,
-            a: io::Result<String>,
-        ) -> io::Result<Sample>
-    {
-        let before = Before::new(&b?)?;
-        let insn   = Insn::new(&i?)?;
-        let after  = After::new(&a?)?;
-
-        Ok(Sample { before, insn, after })
-    }
-}
-
-struct Samples<I>
-    where I: Iterator
-{
-    underlying: Peekable<I>,
-}
-
-impl <I> Samples<I>
-    where I: Iterator
-{
-    fn new(iter: I) -> Samples<I> {
-        Samples { underlying: iter.peekable() }
-    }
-}
-
-fn is_empty_line(l: &io::Result<String>) -> bool {
-    l.as_ref().map(String::is_empty).unwrap_or(false)
-}
-
-impl <I> Iterator for Samples<I>
-    where I: Iterator<Item = io::Result<String>>
-{
-    type Item = io::Result<Sample>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let Samples { ref mut underlying } = self;
-
-        if underlying.peek().map_or(false, is_empty_line) {
-            underlying.next();
-        }
-
-        let b = underlying.next()?;
-        let i = underlying.next()?;
-        let a = underlying.next()?;
-
-        Some(Sample::new(b, i, a))
+record! {
+    struct Sample {
+        before: Before,
+        insn:   Insn,
+        after:  After,
     }
 }
 
@@ -192,35 +139,29 @@ fn resolve_op_codes(mut possible_ops: PossibleOps) -> Translation {
     trn
 }
 
-fn read_program(ix: usize) -> io::Result<std::vec::IntoIter<Insn>>
+/// Reads the test program from the `n`th command-line argument.
+fn read_program(n: usize) -> io::Result<std::vec::IntoIter<Insn>>
 {
-    let fname = env::args().nth(ix).unwrap();
-    let file = File::open(fname)?;
-    let reader = BufReader::new(file);
-
-    let prog_buf = reader.lines()
-        .map(|l| Insn::new(&l?))
-        .collect::<io::Result<Vec<_>>>()?;
+    let input = Source::arg(16, n).load(16)?;
+    let prog_buf = Insn::parse_all(input.reader())
+        .skip_blank()
+        .in_file(&*input.name)
+        .all()?;
 
     Ok(prog_buf.into_iter())
 }
 
 fn main() -> io::Result<()> {
-    let samples = {
-        let fname = env::args().nth(1).unwrap();
-        let file = File::open(fname)?;
-        let reader = BufReader::new(file);
-
-        Samples::new(reader.lines())
-    };
+    let input = input::load(16)?;
+    let samples = input.reader().lines().blocks();
 
     let mut part1 = 0;
     let mut possible_ops: PossibleOps = (0 .. 16)
         .map(|i| (i, ALL_OPS.iter().cloned().collect()))
         .collect();
 
-    for res in samples {
-        let sample = res?;
+    for rec in samples {
+        let sample: Sample = rec?.parse().map_err(|e| e.in_file(&*input.name))?;
         let sample_ops = matching_ops(&sample);
 
         if sample_ops.len() >= 3 {
@@ -1,6 +1,16 @@
+use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
-use std::ops::{Index, IndexMut};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::iter::{self, FromIterator};
+use std::ops::{Add, Index, IndexMut, Sub};
+use std::path::Path;
+use std::slice;
 
+/// Grids are equal, and hash the same, if they have the same dimensions and
+/// elements.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     width: usize,
     height: usize,
@@ -30,6 +40,55 @@ impl <T> Grid<T> {
         self.storage.iter()
     }
 
+    /// Mutable references to each element, in reading order (top to bottom,
+    /// left to right).
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.storage.iter_mut()
+    }
+
+    /// Each element alongside its co-ordinate, in reading order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.storage.iter().enumerate().map(move |(i, t)| ((i % width, i / width), t))
+    }
+
+    /// Each row of the grid as a slice, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        let width = self.width;
+        (0 .. self.height).map(move |y| &self.storage[y * width .. (y + 1) * width])
+    }
+
+    /// Each column of the grid, from left to right, as an iterator over its
+    /// elements from top to bottom.
+    pub fn cols(&self) -> impl Iterator<Item = iter::StepBy<slice::Iter<'_, T>>> {
+        let width = self.width;
+        // A grid with no rows has no storage to start a column from.
+        (0 .. width).map(move |x| self.storage.get(x ..).unwrap_or(&[]).iter().step_by(width))
+    }
+
+    /// A grid of the same size, whose elements are `f` applied to this one's.
+    pub fn map<U, F>(self, f: F) -> Grid<U>
+        where F: FnMut(T) -> U
+    {
+        let Grid { width, height, storage } = self;
+        let storage = storage.into_vec().into_iter().map(f).collect::<Vec<U>>().into_boxed_slice();
+        Grid { width, height, storage }
+    }
+
+    /// Pairs of elements at the same co-ordinates in this grid and `other`,
+    /// in reading order.
+    ///
+    /// Panics if the grids are not the same size.
+    pub fn zip<'a, U>(&'a self, other: &'a Grid<U>) -> iter::Zip<slice::Iter<'a, T>, slice::Iter<'a, U>> {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "Cannot zip {}x{} grid with {}x{} grid",
+            self.width, self.height, other.width, other.height,
+        );
+
+        self.storage.iter().zip(other.storage.iter())
+    }
+
     pub fn coord(&self, i: usize) -> (usize, usize) {
         let x = i % self.width;
         let y = i / self.width;
@@ -43,23 +102,75 @@ impl <T> Grid<T> {
     pub fn height(&self) -> usize {
         self.height
     }
+
+    /// Position of the element at `(x, y)` in storage, if it is in bounds.
+    fn offset_of(&self, (x, y): (usize, usize)) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, pos: (usize, usize)) -> bool {
+        self.offset_of(pos).is_some()
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.offset_of(pos).map(|i| &self.storage[i])
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        self.offset_of(pos).map(move |i| &mut self.storage[i])
+    }
+
+    /// The co-ordinate `delta` away from `pos`, if it is in bounds.
+    pub fn step(&self, pos: (usize, usize), delta: (isize, isize)) -> Option<(usize, usize)> {
+        let (x, y) = pos;
+        let (dx, dy) = delta;
+        let x = (x as isize).checked_add(dx)?;
+        let y = (y as isize).checked_add(dy)?;
+
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        let next = (x as usize, y as usize);
+        if self.contains(next) { Some(next) } else { None }
+    }
+
+    /// Co-ordinates of the (up to) 4 cells orthogonally adjacent to `pos`, in
+    /// reading order (top to bottom, left to right).
+    pub fn neighbours4(&self, pos: (usize, usize)) -> Neighbours {
+        Neighbours { pos, width: self.width, height: self.height, deltas: DELTAS4.iter() }
+    }
+
+    /// Co-ordinates of the (up to) 8 cells orthogonally or diagonally adjacent
+    /// to `pos`, in reading order.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> Neighbours {
+        Neighbours { pos, width: self.width, height: self.height, deltas: DELTAS8.iter() }
+    }
 }
 
 impl <T> Index<(usize, usize)> for Grid<T> {
     type Output = T;
 
     fn index<'a>(&'a self, index: (usize, usize)) -> &'a T {
-        let (x, y) = index;
-        let i = y * self.width + x;
-        &self.storage[i]
+        match self.offset_of(index) {
+            Some(i) => &self.storage[i],
+            None => panic!(
+                "Index {:?} out of bounds for {}x{} grid", index, self.width, self.height),
+        }
     }
 }
 
 impl <T> IndexMut<(usize, usize)> for Grid<T> {
     fn index_mut<'a>(&'a mut self, index: (usize, usize)) -> &'a mut T {
-        let (x, y) = index;
-        let i = y * self.width + x;
-        &mut self.storage[i]
+        match self.offset_of(index) {
+            Some(i) => &mut self.storage[i],
+            None => panic!(
+                "Index {:?} out of bounds for {}x{} grid", index, self.width, self.height),
+        }
     }
 }
 
@@ -78,3 +189,1133 @@ impl <T> fmt::Debug for Grid<T>
         Ok(())
     }
 }
+
+/// Offsets to orthogonally adjacent cells, in reading order.
+static DELTAS4: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Offsets to orthogonally or diagonally adjacent cells, in reading order.
+static DELTAS8: [(isize, isize); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1,  0),          (1,  0),
+    (-1,  1), (0,  1), (1,  1),
+];
+
+/// An iterator over the co-ordinates adjacent to a cell in a grid, skipping
+/// those that fall outside it.  It does not borrow the grid, so that the
+/// grid can be modified while iterating.
+///
+/// Constructed by a call to [`neighbours4`] or [`neighbours8`].
+///
+/// [`neighbours4`]: struct.Grid.html#method.neighbours4
+/// [`neighbours8`]: struct.Grid.html#method.neighbours8
+pub struct Neighbours {
+    pos: (usize, usize),
+    width: usize,
+    height: usize,
+    deltas: slice::Iter<'static, (isize, isize)>,
+}
+
+impl Iterator for Neighbours {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let (x, y) = self.pos;
+        for &(dx, dy) in &mut self.deltas {
+            let nx = x.wrapping_add(dx as usize);
+            let ny = y.wrapping_add(dy as usize);
+
+            // Wrapping below zero produces a large value, also out of bounds.
+            if nx < self.width && ny < self.height {
+                return Some((nx, ny));
+            }
+        }
+
+        None
+    }
+}
+
+/// Collects a grid from its rows, from top to bottom.
+///
+/// Panics if the rows are not all the same length.
+impl <T, R> FromIterator<R> for Grid<T> where R: IntoIterator<Item = T> {
+    fn from_iter<I: IntoIterator<Item = R>>(rows: I) -> Grid<T> {
+        let mut storage = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for row in rows {
+            let before = storage.len();
+            storage.extend(row);
+
+            let len = storage.len() - before;
+            if let Some(width) = width {
+                assert!(
+                    width == len,
+                    "Row {} has {} elements, but the rows before it have {}",
+                    height, len, width,
+                );
+            }
+
+            width = Some(len);
+            height += 1;
+        }
+
+        Grid { width: width.unwrap_or(0), height, storage: storage.into_boxed_slice() }
+    }
+}
+
+impl <T> Grid<T> where T: Clone + Default {
+    /// Parses a grid from lines of text, mapping each byte to a cell with
+    /// `f(x, y, byte)`.  `f` can also collect side data, such as the
+    /// positions of units on a map.  The grid is as wide as the longest line,
+    /// and shorter lines are padded with default cells.
+    ///
+    /// Fails at the first byte that `f` does not map to a cell.
+    pub fn parse<F>(text: &str, mut f: F) -> Result<Grid<T>, BadCell>
+        where F: FnMut(usize, usize, u8) -> Option<T>
+    {
+        let lines: Vec<&[u8]> = text.lines().map(str::as_bytes).collect();
+        let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+
+        let mut grid = Grid::new(width, lines.len(), T::default());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, &byte) in line.iter().enumerate() {
+                grid[(x, y)] = f(x, y, byte).ok_or(BadCell { x, y, byte })?;
+            }
+        }
+
+        Ok(grid)
+    }
+}
+
+/// Error for when a byte of text could not be parsed as a cell of a grid.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct BadCell {
+    pub x: usize,
+    pub y: usize,
+    pub byte: u8,
+}
+
+impl fmt::Display for BadCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unexpected {:?} in grid at {},{}", self.byte as char, self.x, self.y)
+    }
+}
+
+impl fmt::Debug for BadCell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Error for BadCell {}
+
+impl From<BadCell> for io::Error {
+    fn from(err: BadCell) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+impl <T> Grid<T> where T: Clone {
+    /// A copy of the grid rotated a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Grid<T> {
+        let h = self.height;
+        Grid::new_with_mapping(h, self.width, |x, y| self[(y, h - 1 - x)].clone())
+    }
+
+    /// A copy of the grid mirrored left to right.
+    pub fn flip_h(&self) -> Grid<T> {
+        let w = self.width;
+        Grid::new_with_mapping(w, self.height, |x, y| self[(w - 1 - x, y)].clone())
+    }
+
+    /// A copy of the grid mirrored along the diagonal from its top-left
+    /// corner, so that its rows become columns.
+    pub fn transpose(&self) -> Grid<T> {
+        Grid::new_with_mapping(self.height, self.width, |x, y| self[(y, x)].clone())
+    }
+}
+
+impl <T> Grid<T> {
+    /// A view of the `w` by `h` rectangle whose top-left corner is at
+    /// `(x, y)`, without copying it.
+    ///
+    /// Panics if the rectangle does not fit in the grid.
+    pub fn view(&self, (x, y): (usize, usize), (w, h): (usize, usize)) -> View<'_, T> {
+        assert!(
+            x + w <= self.width && y + h <= self.height,
+            "View {}x{} at {:?} out of bounds for {}x{} grid",
+            w, h, (x, y), self.width, self.height,
+        );
+
+        View { grid: self, left: x, top: y, width: w, height: h }
+    }
+
+    /// Views of every `w` by `h` rectangle in the grid, in reading order of
+    /// their top-left corners.  There are none if the grid is smaller than a
+    /// window.
+    pub fn windows(&self, w: usize, h: usize) -> impl Iterator<Item = View<'_, T>> {
+        assert!(w > 0 && h > 0, "Windows must not be empty");
+        let across = (self.width + 1).saturating_sub(w);
+        let down = (self.height + 1).saturating_sub(h);
+        (0 .. across * down).map(move |i| self.view((i % across, i / across), (w, h)))
+    }
+}
+
+/// A rectangular part of a grid, indexed relative to its top-left corner.
+///
+/// Constructed by a call to [`view`] or [`windows`].
+///
+/// [`view`]: struct.Grid.html#method.view
+/// [`windows`]: struct.Grid.html#method.windows
+pub struct View<'g, T: 'g> {
+    grid: &'g Grid<T>,
+    left: usize,
+    top: usize,
+    width: usize,
+    height: usize,
+}
+
+impl <'g, T> Clone for View<'g, T> {
+    fn clone(&self) -> View<'g, T> {
+        *self
+    }
+}
+
+impl <'g, T> Copy for View<'g, T> {}
+
+impl <'g, T> View<'g, T> {
+    /// Position of the view's top-left corner in the underlying grid.
+    pub fn origin(&self) -> (usize, usize) {
+        (self.left, self.top)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<&'g T> {
+        if self.contains((x, y)) {
+            Some(&self.grid[(self.left + x, self.top + y)])
+        } else {
+            None
+        }
+    }
+
+    /// Each row of the view as a slice, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &'g [T]> {
+        let View { grid, left, top, width, height } = *self;
+        (top .. top + height).map(move |y| {
+            let start = y * grid.width + left;
+            &grid.storage[start .. start + width]
+        })
+    }
+
+    /// Each element of the view, in reading order.
+    pub fn elems(&self) -> impl Iterator<Item = &'g T> {
+        self.rows().flat_map(|row| row.iter())
+    }
+
+    /// Each element alongside its co-ordinate within the view, in reading
+    /// order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((usize, usize), &'g T)> {
+        let width = self.width;
+        self.elems().enumerate().map(move |(i, t)| ((i % width, i / width), t))
+    }
+}
+
+impl <'g, T> View<'g, T> where T: Clone {
+    /// A copy of the view as a grid of its own.
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::new_with_mapping(self.width, self.height, |x, y| self[(x, y)].clone())
+    }
+}
+
+impl <'g, T> Index<(usize, usize)> for View<'g, T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize)) -> &T {
+        match self.get(index) {
+            Some(elem) => elem,
+            None => panic!(
+                "Index {:?} out of bounds for {}x{} view", index, self.width, self.height),
+        }
+    }
+}
+
+/// Types that can be drawn as a single character in a grid.
+pub trait CellChar {
+    fn cell_char(&self) -> char;
+}
+
+impl CellChar for char {
+    fn cell_char(&self) -> char {
+        *self
+    }
+}
+
+/// Live cells are drawn as `#`, and dead ones as `.`.
+impl CellChar for bool {
+    fn cell_char(&self) -> char {
+        if *self { '#' } else { '.' }
+    }
+}
+
+/// Draws a grid one character per cell, with a line per row.
+impl <T> fmt::Display for Grid<T> where T: CellChar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+type Overlay<'g, T> = Box<dyn Fn(usize, usize, &T) -> Option<char> + 'g>;
+type Annotation<'g> = Box<dyn Fn(usize) -> String + 'g>;
+
+/// Draws a grid one character per cell, like its `Display` implementation,
+/// with optional overlays and per-row annotations.
+///
+/// Constructed by a call to [`render`].
+///
+/// [`render`]: struct.Grid.html#method.render
+pub struct Render<'g, T: 'g> {
+    grid: &'g Grid<T>,
+    overlay: Option<Overlay<'g, T>>,
+    annotate: Option<Annotation<'g>>,
+}
+
+impl <T> Grid<T> where T: CellChar {
+    pub fn render(&self) -> Render<'_, T> {
+        Render { grid: self, overlay: None, annotate: None }
+    }
+}
+
+impl <'g, T> Render<'g, T> where T: CellChar {
+    /// Draws `f(x, y, cell)` in place of any cell for which it is not `None`,
+    /// e.g. to draw entities over terrain.
+    pub fn overlay<F>(self, f: F) -> Render<'g, T>
+        where F: Fn(usize, usize, &T) -> Option<char> + 'g
+    {
+        Render { overlay: Some(Box::new(f)), ..self }
+    }
+
+    /// Writes `f(y)` after row `y`, separated from it by a space if it is not
+    /// empty.
+    pub fn annotate<F>(self, f: F) -> Render<'g, T>
+        where F: Fn(usize) -> String + 'g
+    {
+        Render { annotate: Some(Box::new(f)), ..self }
+    }
+}
+
+impl <'g, T> fmt::Display for Render<'g, T> where T: CellChar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let grid = self.grid;
+        for y in 0 .. grid.height {
+            for x in 0 .. grid.width {
+                let cell = &grid[(x, y)];
+                let over = self.overlay.as_ref().and_then(|o| o(x, y, cell));
+                write!(f, "{}", over.unwrap_or_else(|| cell.cell_char()))?;
+            }
+
+            if let Some(annotate) = &self.annotate {
+                let note = annotate(y);
+                if !note.is_empty() {
+                    write!(f, " {}", note)?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Kinds of binary Netpbm image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Netpbm {
+    /// PBM: one bit per pixel, set for black.
+    Bitmap,
+    /// PGM: one byte per pixel, from black to white.
+    Greymap,
+    /// PPM: three bytes per pixel, for red, green and blue.
+    Pixmap,
+}
+
+type Samples<'g, T> = Box<dyn Fn(&T) -> [u8; 3] + 'g>;
+
+/// A grid drawn as a Netpbm image, one pixel (or square of pixels, if scaled)
+/// per cell.
+///
+/// Constructed by a call to [`pbm`], [`pgm`] or [`ppm`].
+///
+/// [`pbm`]: struct.Grid.html#method.pbm
+/// [`pgm`]: struct.Grid.html#method.pgm
+/// [`ppm`]: struct.Grid.html#method.ppm
+pub struct Image<'g, T: 'g> {
+    grid: &'g Grid<T>,
+    format: Netpbm,
+    /// Samples for each cell.  Only the first is used by bitmaps and
+    /// greymaps.
+    samples: Samples<'g, T>,
+    scale: usize,
+}
+
+impl <T> Grid<T> {
+    /// A black and white image of the grid, with black pixels for the cells
+    /// where `f(cell)` is true.
+    pub fn pbm<'g, F>(&'g self, f: F) -> Image<'g, T>
+        where F: Fn(&T) -> bool + 'g
+    {
+        self.image(Netpbm::Bitmap, Box::new(move |t| [f(t) as u8, 0, 0]))
+    }
+
+    /// A greyscale image of the grid, with intensity `f(cell)`, from black
+    /// (0) to white (255).
+    pub fn pgm<'g, F>(&'g self, f: F) -> Image<'g, T>
+        where F: Fn(&T) -> u8 + 'g
+    {
+        self.image(Netpbm::Greymap, Box::new(move |t| [f(t), 0, 0]))
+    }
+
+    /// A colour image of the grid, with red, green and blue intensities
+    /// `f(cell)`.
+    pub fn ppm<'g, F>(&'g self, f: F) -> Image<'g, T>
+        where F: Fn(&T) -> [u8; 3] + 'g
+    {
+        self.image(Netpbm::Pixmap, Box::new(f))
+    }
+
+    fn image<'g>(&'g self, format: Netpbm, samples: Samples<'g, T>) -> Image<'g, T> {
+        Image { grid: self, format, samples, scale: 1 }
+    }
+}
+
+impl <'g, T> Image<'g, T> {
+    /// Draws each cell as a `scale` by `scale` square of pixels.
+    pub fn scale(self, scale: usize) -> Image<'g, T> {
+        assert!(scale > 0, "Cells must be at least one pixel wide");
+        Image { scale, ..self }
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width * self.scale
+    }
+
+    pub fn height(&self) -> usize {
+        self.grid.height * self.scale
+    }
+
+    /// Writes the image out in the binary variant of its format.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        match self.format {
+            Netpbm::Bitmap => write!(out, "P4\n{} {}\n", self.width(), self.height())?,
+            Netpbm::Greymap => write!(out, "P5\n{} {}\n255\n", self.width(), self.height())?,
+            Netpbm::Pixmap => write!(out, "P6\n{} {}\n255\n", self.width(), self.height())?,
+        }
+
+        let mut line = vec![];
+        for row in self.grid.rows() {
+            line.clear();
+            for cell in row {
+                let samples = (self.samples)(cell);
+                for _ in 0 .. self.scale {
+                    match self.format {
+                        Netpbm::Bitmap | Netpbm::Greymap => line.push(samples[0]),
+                        Netpbm::Pixmap => line.extend_from_slice(&samples),
+                    }
+                }
+            }
+
+            if self.format == Netpbm::Bitmap {
+                // Pack bits into bytes, most significant first, padding the
+                // end of each line.
+                line = line.chunks(8)
+                    .map(|bits| bits.iter().enumerate().fold(0, |byte, (i, &bit)| {
+                        byte | (bit << (7 - i))
+                    }))
+                    .collect();
+            }
+
+            for _ in 0 .. self.scale {
+                out.write_all(&line)?;
+            }
+        }
+
+        out.flush()
+    }
+
+    /// Writes the image to the file at `path`, replacing it if it exists.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Sums of the elements of a grid, from which the sum of any rectangle of it
+/// can be found in constant time.
+pub struct SummedAreaTable<T> {
+    /// `sums[(x, y)]` is the sum of the elements above and to the left of
+    /// `(x, y)` in the original grid, so the table has an extra row and
+    /// column of zeroes at its top and left.
+    sums: Grid<T>,
+}
+
+impl <T> SummedAreaTable<T>
+    where T: Add<Output = T> + Sub<Output = T> + Copy + Default
+{
+    pub fn new(grid: &Grid<T>) -> SummedAreaTable<T> {
+        let mut sums = Grid::new(grid.width + 1, grid.height + 1, T::default());
+        for ((x, y), &t) in grid.iter_coords() {
+            sums[(x + 1, y + 1)] = t + sums[(x, y + 1)] + sums[(x + 1, y)] - sums[(x, y)];
+        }
+
+        SummedAreaTable { sums }
+    }
+
+    /// Width of the summed grid.
+    pub fn width(&self) -> usize {
+        self.sums.width - 1
+    }
+
+    /// Height of the summed grid.
+    pub fn height(&self) -> usize {
+        self.sums.height - 1
+    }
+
+    /// Sum of the `w` by `h` rectangle whose top-left corner is at `(x, y)`.
+    ///
+    /// Panics if the rectangle does not fit in the grid.
+    pub fn sum(&self, (x, y): (usize, usize), (w, h): (usize, usize)) -> T {
+        let (r, b) = (x + w, y + h);
+        assert!(
+            r <= self.width() && b <= self.height(),
+            "Rectangle {}x{} at {:?} out of bounds for {}x{} grid",
+            w, h, (x, y), self.width(), self.height(),
+        );
+
+        self.sums[(r, b)] + self.sums[(x, y)] - self.sums[(x, b)] - self.sums[(r, y)]
+    }
+}
+
+impl <T> SummedAreaTable<T>
+    where T: Add<Output = T> + Sub<Output = T> + Copy + Default + Ord
+{
+    /// Top-left corner and sum of the `k` by `k` square with the largest sum.
+    /// Ties go to the first square in reading order.  `None` if there are no
+    /// such squares, because `k` is zero or larger than the grid.
+    pub fn best_window(&self, k: usize) -> Option<((usize, usize), T)> {
+        if k == 0 || k > self.width() || k > self.height() {
+            return None;
+        }
+
+        let mut best = None;
+        for y in 0 ..= self.height() - k {
+            for x in 0 ..= self.width() - k {
+                let sum = self.sum((x, y), (k, k));
+                if best.is_none_or(|(_, max)| sum > max) {
+                    best = Some(((x, y), sum));
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Top-left corner, size and sum of the square of any size with the
+    /// largest sum.  Ties go to the smallest square, and then the first in
+    /// reading order.  `None` if the grid is empty.
+    pub fn best_window_any(&self) -> Option<((usize, usize), usize, T)> {
+        let mut best = None;
+        for k in 1 ..= self.width().min(self.height()) {
+            let (pos, sum) = self.best_window(k)?;
+            if best.is_none_or(|(_, _, max)| sum > max) {
+                best = Some((pos, k, sum));
+            }
+        }
+
+        best
+    }
+}
+
+/// A rectangle of points with signed co-ordinates, including its edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Bounds {
+    pub left: isize,
+    pub top: isize,
+    pub right: isize,
+    pub bottom: isize,
+}
+
+impl Bounds {
+    /// The smallest bounds containing all of `points`, or `None` if there
+    /// are none.
+    pub fn around<I>(points: I) -> Option<Bounds>
+        where I: IntoIterator<Item = (isize, isize)>
+    {
+        let mut points = points.into_iter();
+        let (x, y) = points.next()?;
+        let bounds = Bounds { left: x, top: y, right: x, bottom: y };
+        Some(points.fold(bounds, Bounds::including))
+    }
+
+    /// The smallest bounds containing these bounds and `(x, y)`.
+    pub fn including(self, (x, y): (isize, isize)) -> Bounds {
+        Bounds {
+            left: self.left.min(x),
+            top: self.top.min(y),
+            right: self.right.max(x),
+            bottom: self.bottom.max(y),
+        }
+    }
+
+    pub fn contains(&self, (x, y): (isize, isize)) -> bool {
+        self.left <= x && x <= self.right && self.top <= y && y <= self.bottom
+    }
+
+    pub fn width(&self) -> usize {
+        assert!(self.left <= self.right);
+        (self.right - self.left + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        assert!(self.top <= self.bottom);
+        (self.bottom - self.top + 1) as usize
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+
+    /// The position of `(x, y)` relative to the top-left corner, if it is
+    /// within bounds.
+    pub fn to_local(&self, (x, y): (isize, isize)) -> Option<(usize, usize)> {
+        if self.contains((x, y)) {
+            Some(((x - self.left) as usize, (y - self.top) as usize))
+        } else {
+            None
+        }
+    }
+
+    /// The point at position `(i, j)` relative to the top-left corner.
+    pub fn to_global(&self, (i, j): (usize, usize)) -> (isize, isize) {
+        (self.left + i as isize, self.top + j as isize)
+    }
+}
+
+/// A grid covering a rectangle of signed co-ordinates, which need not start
+/// at the origin.
+pub struct OffsetGrid<T> {
+    bounds: Bounds,
+    grid: Grid<T>,
+}
+
+impl <T> OffsetGrid<T> where T: Clone {
+    pub fn new(bounds: Bounds, init: T) -> OffsetGrid<T> {
+        let grid = Grid::new(bounds.width(), bounds.height(), init);
+        OffsetGrid { bounds, grid }
+    }
+}
+
+impl <T> OffsetGrid<T> {
+    pub fn new_with_mapping<F>(bounds: Bounds, mut f: F) -> OffsetGrid<T>
+        where F: FnMut(isize, isize) -> T
+    {
+        let grid = Grid::new_with_mapping(bounds.width(), bounds.height(), |i, j| {
+            let (x, y) = bounds.to_global((i, j));
+            f(x, y)
+        });
+
+        OffsetGrid { bounds, grid }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// The underlying grid, indexed relative to the top-left corner.
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.grid
+    }
+
+    pub fn contains(&self, pos: (isize, isize)) -> bool {
+        self.bounds.contains(pos)
+    }
+
+    pub fn get(&self, pos: (isize, isize)) -> Option<&T> {
+        self.bounds.to_local(pos).map(|pos| &self.grid[pos])
+    }
+
+    pub fn get_mut(&mut self, pos: (isize, isize)) -> Option<&mut T> {
+        let grid = &mut self.grid;
+        self.bounds.to_local(pos).map(move |pos| &mut grid[pos])
+    }
+}
+
+impl <T> Index<(isize, isize)> for OffsetGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: (isize, isize)) -> &T {
+        match self.get(index) {
+            Some(elem) => elem,
+            None => panic!("Index {:?} out of bounds {:?}", index, self.bounds),
+        }
+    }
+}
+
+impl <T> IndexMut<(isize, isize)> for OffsetGrid<T> {
+    fn index_mut(&mut self, index: (isize, isize)) -> &mut T {
+        let bounds = self.bounds;
+        match self.get_mut(index) {
+            Some(elem) => elem,
+            None => panic!("Index {:?} out of bounds {:?}", index, bounds),
+        }
+    }
+}
+
+/// An unbounded grid with signed co-ordinates, that only stores the cells
+/// written to.  Every other cell holds a default value.
+pub struct SparseGrid<T> {
+    default: T,
+    cells: HashMap<(isize, isize), T>,
+    /// Bounds around every cell written to, even if it was later removed.
+    bounds: Option<Bounds>,
+}
+
+impl <T> SparseGrid<T> {
+    pub fn new(default: T) -> SparseGrid<T> {
+        SparseGrid { default, cells: HashMap::new(), bounds: None }
+    }
+
+    /// The smallest bounds containing every cell written to so far, or `None`
+    /// if there have been none.
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    /// Number of cells written to, and not since removed.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Whether the cell at `pos` has been written to.
+    pub fn contains(&self, pos: (isize, isize)) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    /// The cell at `pos`, if it has been written to.
+    pub fn get(&self, pos: (isize, isize)) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: (isize, isize)) -> Option<&mut T> {
+        self.cells.get_mut(&pos)
+    }
+
+    /// Writes `value` to the cell at `pos`, returning its previous value if
+    /// it had been written to.
+    pub fn insert(&mut self, pos: (isize, isize), value: T) -> Option<T> {
+        self.include(pos);
+        self.cells.insert(pos, value)
+    }
+
+    /// Resets the cell at `pos` to the default value, returning its previous
+    /// value if it had been written to.  The bounds do not shrink.
+    pub fn remove(&mut self, pos: (isize, isize)) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    /// Cells written to, alongside their co-ordinates, in no particular
+    /// order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((isize, isize), &T)> {
+        self.cells.iter().map(|(&pos, t)| (pos, t))
+    }
+
+    /// Cells written to, in no particular order.
+    pub fn elems(&self) -> impl Iterator<Item = &T> {
+        self.cells.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.values_mut()
+    }
+
+    /// Co-ordinates of the 4 cells orthogonally adjacent to `pos`, in reading
+    /// order.
+    pub fn neighbours4(&self, (x, y): (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
+        DELTAS4.iter().map(move |&(dx, dy)| (x + dx, y + dy))
+    }
+
+    /// Co-ordinates of the 8 cells orthogonally or diagonally adjacent to
+    /// `pos`, in reading order.
+    pub fn neighbours8(&self, (x, y): (isize, isize)) -> impl Iterator<Item = (isize, isize)> {
+        DELTAS8.iter().map(move |&(dx, dy)| (x + dx, y + dy))
+    }
+
+    fn include(&mut self, pos: (isize, isize)) {
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.including(pos),
+            None => Bounds { left: pos.0, top: pos.1, right: pos.0, bottom: pos.1 },
+        });
+    }
+}
+
+impl <T> SparseGrid<T> where T: Clone {
+    /// A dense copy of the cells within the bounds, or `None` if no cells
+    /// have been written to.
+    pub fn to_offset_grid(&self) -> Option<OffsetGrid<T>> {
+        let bounds = self.bounds?;
+        Some(OffsetGrid::new_with_mapping(bounds, |x, y| self[(x, y)].clone()))
+    }
+
+    /// A dense copy of the cells within the bounds, indexed relative to
+    /// their top-left corner, e.g. for rendering.  Empty if no cells have
+    /// been written to.
+    pub fn to_grid(&self) -> Grid<T> {
+        match self.to_offset_grid() {
+            Some(grid) => grid.into_grid(),
+            None => Grid::new(0, 0, self.default.clone()),
+        }
+    }
+}
+
+/// Cells that have not been written to hold the default value.
+impl <T> Index<(isize, isize)> for SparseGrid<T> {
+    type Output = T;
+
+    fn index(&self, index: (isize, isize)) -> &T {
+        self.cells.get(&index).unwrap_or(&self.default)
+    }
+}
+
+/// Writing through a cell that has not been written to before sets it to the
+/// default value first.
+impl <T> IndexMut<(isize, isize)> for SparseGrid<T> where T: Clone {
+    fn index_mut(&mut self, index: (isize, isize)) -> &mut T {
+        self.include(index);
+        let SparseGrid { default, cells, .. } = self;
+        cells.entry(index).or_insert_with(|| default.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grid::{BadCell, Bounds, Grid, OffsetGrid, SparseGrid, SummedAreaTable};
+
+    #[test]
+    fn get() {
+        let mut grid = Grid::new_with_mapping(3, 2, |x, y| x + 10 * y);
+        assert_eq!(grid.get((2, 1)), Some(&12));
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+
+        *grid.get_mut((1, 1)).unwrap() = 0;
+        assert_eq!(grid[(1, 1)], 0);
+        assert!(grid.get_mut((usize::MAX, 0)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn index_does_not_wrap() {
+        let grid = Grid::new(3, 2, 0);
+        let _ = &grid[(3, 0)];
+    }
+
+    #[test]
+    fn step() {
+        let grid = Grid::new(3, 2, 0);
+        assert_eq!(grid.step((1, 1), (1, -1)), Some((2, 0)));
+        assert_eq!(grid.step((0, 0), (-1, 0)), None);
+        assert_eq!(grid.step((2, 1), (0, 1)), None);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        let n4 = |pos| grid.neighbours4(pos).collect::<Vec<_>>();
+        let n8 = |pos| grid.neighbours8(pos).collect::<Vec<_>>();
+
+        assert_eq!(n4((1, 1)), [(1, 0), (0, 1), (2, 1), (1, 2)]);
+        assert_eq!(n4((0, 0)), [(1, 0), (0, 1)]);
+        assert_eq!(n4((2, 2)), [(2, 1), (1, 2)]);
+
+        assert_eq!(n8((1, 1)).len(), 8);
+        assert_eq!(n8((0, 2)), [(0, 1), (1, 1), (1, 2)]);
+        assert_eq!(n8((2, 0)), [(1, 0), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::around(vec![(1, -2), (-3, 4), (0, 0)]).unwrap();
+        assert_eq!(bounds, Bounds { left: -3, top: -2, right: 1, bottom: 4 });
+        assert_eq!((bounds.width(), bounds.height(), bounds.area()), (5, 7, 35));
+
+        assert!(bounds.contains((-3, 4)));
+        assert!(!bounds.contains((2, 0)));
+        assert_eq!(bounds.to_local((-3, -2)), Some((0, 0)));
+        assert_eq!(bounds.to_local((-4, 0)), None);
+        assert_eq!(bounds.to_global((4, 6)), (1, 4));
+
+        assert_eq!(Bounds::around(vec![]), None);
+    }
+
+    #[test]
+    fn offset_grid() {
+        let bounds = Bounds { left: -1, top: -1, right: 1, bottom: 1 };
+        let mut grid = OffsetGrid::new_with_mapping(bounds, |x, y| x * y);
+        assert_eq!(grid[(-1, 1)], -1);
+        assert_eq!(grid.get((2, 0)), None);
+
+        grid[(0, -1)] = 5;
+        assert_eq!(grid.grid()[(1, 0)], 5);
+        assert!(grid.get_mut((0, -2)).is_none());
+    }
+
+    #[test]
+    fn parse() {
+        let mut stars = vec![];
+        let grid = Grid::parse("#.*\n.\n\n*", |x, y, b| {
+            if b == b'*' {
+                stars.push((x, y));
+            }
+
+            match b {
+                b'#' => Some(2),
+                b'.' | b'*' => Some(1),
+                _ => None,
+            }
+        }).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 4));
+        assert_eq!(grid.elems().cloned().collect::<Vec<_>>(), [2, 1, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(stars, [(2, 0), (0, 3)]);
+
+        let err = Grid::<u8>::parse("..\n.x", |_, _, b| if b == b'.' { Some(1) } else { None });
+        assert_eq!(err.unwrap_err(), BadCell { x: 1, y: 1, byte: b'x' });
+        assert_eq!(
+            BadCell { x: 1, y: 1, byte: b'x' }.to_string(),
+            "Unexpected 'x' in grid at 1,1",
+        );
+    }
+
+    #[test]
+    fn display() {
+        let grid = Grid::new_with_mapping(3, 2, |x, y| x == y);
+        assert_eq!(grid.to_string(), "#..\n.#.\n");
+
+        let render = grid.render()
+            .overlay(|x, _, &live| if live && x > 0 { Some('@') } else { None })
+            .annotate(|y| if y == 0 { "top".to_owned() } else { String::new() });
+
+        assert_eq!(render.to_string(), "#.. top\n.@.\n");
+    }
+
+    #[test]
+    fn iterators() {
+        let mut grid: Grid<usize> = vec![vec![1, 2, 3], vec![4, 5, 6]].into_iter().collect();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+
+        let rows: Vec<&[usize]> = grid.rows().collect();
+        assert_eq!(rows, [&[1, 2, 3], &[4, 5, 6]]);
+
+        let cols: Vec<Vec<usize>> = grid.cols().map(|c| c.cloned().collect()).collect();
+        assert_eq!(cols, [[1, 4], [2, 5], [3, 6]]);
+
+        let coords: Vec<((usize, usize), usize)> =
+            grid.iter_coords().map(|(pos, &t)| (pos, t)).collect();
+        assert_eq!(coords[0], ((0, 0), 1));
+        assert_eq!(coords[4], ((1, 1), 5));
+
+        for t in grid.iter_mut() {
+            *t *= 10;
+        }
+        assert_eq!(grid[(2, 1)], 60);
+
+        let odd = Grid::new_with_mapping(3, 2, |x, y| (x + y) % 2 == 1);
+        let sum: usize = grid.zip(&odd).filter(|&(_, &o)| o).map(|(&t, _)| t).sum();
+        assert_eq!(sum, 20 + 40 + 60);
+
+        let halves = grid.map(|t| t / 2);
+        assert_eq!(halves.elems().cloned().collect::<Vec<_>>(), [5, 10, 15, 20, 25, 30]);
+
+        let empty: Grid<usize> = Vec::<Vec<usize>>::new().into_iter().collect();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.cols().count(), 0);
+
+        let flat = Grid::new(3, 0, 0u8);
+        assert_eq!(flat.rows().count(), 0);
+        assert_eq!(flat.cols().map(|c| c.count()).collect::<Vec<_>>(), [0, 0, 0]);
+
+        let thin: Grid<usize> = vec![vec![], vec![]].into_iter().collect();
+        assert_eq!((thin.width(), thin.height()), (0, 2));
+        assert_eq!(thin.rows().map(|r| r.len()).collect::<Vec<_>>(), [0, 0]);
+        assert_eq!(thin.cols().count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Row 1 has 1 elements")]
+    fn ragged_rows() {
+        let _: Grid<usize> = vec![vec![1, 2], vec![3]].into_iter().collect();
+    }
+
+    #[test]
+    fn summed_area_table() {
+        let grid: Grid<isize> = vec![
+            vec![ 1,  2, -1],
+            vec![-3,  4,  5],
+            vec![ 0, -2,  6],
+        ].into_iter().collect();
+
+        let sat = SummedAreaTable::new(&grid);
+        assert_eq!(sat.sum((0, 0), (3, 3)), 12);
+        assert_eq!(sat.sum((1, 1), (2, 2)), 13);
+        assert_eq!(sat.sum((2, 0), (1, 2)), 4);
+        assert_eq!(sat.sum((1, 1), (0, 2)), 0);
+
+        assert_eq!(sat.best_window(1), Some(((2, 2), 6)));
+        assert_eq!(sat.best_window(2), Some(((1, 1), 13)));
+        assert_eq!(sat.best_window(0), None);
+        assert_eq!(sat.best_window(4), None);
+        assert_eq!(sat.best_window_any(), Some(((1, 1), 2, 13)));
+
+        let zeroes = SummedAreaTable::new(&Grid::new(2, 2, 0));
+        assert_eq!(zeroes.best_window_any(), Some(((0, 0), 1, 0)));
+
+        let empty = SummedAreaTable::new(&Grid::new(0, 0, 0));
+        assert_eq!(empty.best_window_any(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn summed_area_out_of_bounds() {
+        SummedAreaTable::new(&Grid::new(2, 2, 0)).sum((1, 0), (2, 1));
+    }
+
+    #[test]
+    fn sparse_grid() {
+        let mut grid = SparseGrid::new('.');
+        assert_eq!(grid.bounds(), None);
+        assert_eq!(grid[(-5, 7)], '.');
+        assert_eq!(grid.to_grid().width(), 0);
+
+        grid[(-2, 1)] = '#';
+        assert_eq!(grid.insert((3, -1), '~'), None);
+        assert_eq!(grid.insert((3, -1), '|'), Some('~'));
+        assert_eq!(grid.len(), 2);
+        assert!(grid.contains((-2, 1)));
+        assert!(!grid.contains((0, 0)));
+        assert_eq!(grid.get((0, 0)), None);
+        assert_eq!(grid.bounds(), Some(Bounds { left: -2, top: -1, right: 3, bottom: 1 }));
+
+        let dense = grid.to_offset_grid().unwrap();
+        assert_eq!(dense[(3, -1)], '|');
+        assert_eq!(grid.to_grid().to_string(), ".....|\n......\n#.....\n");
+
+        // Removing cells does not shrink the bounds.
+        assert_eq!(grid.remove((3, -1)), Some('|'));
+        assert_eq!(grid[(3, -1)], '.');
+        assert_eq!(grid.bounds().unwrap().right, 3);
+
+        let nbrs: Vec<_> = grid.neighbours4((0, 0)).collect();
+        assert_eq!(nbrs, [(0, -1), (-1, 0), (1, 0), (0, 1)]);
+        assert_eq!(grid.neighbours8((-2, 1)).filter(|&n| grid[n] == '#').count(), 0);
+
+        for t in grid.iter_mut() {
+            *t = '@';
+        }
+        assert_eq!(grid.iter_coords().collect::<Vec<_>>(), [((-2, 1), &'@')]);
+    }
+
+    #[test]
+    fn images() {
+        let grid = Grid::new_with_mapping(3, 2, |x, y| x + 3 * y);
+
+        let mut pbm = vec![];
+        grid.pbm(|&t| t % 2 == 0).scale(3).write_to(&mut pbm).unwrap();
+        let row0: &[u8] = &[0b1110_0011, 0b1000_0000];
+        let row1: &[u8] = &[0b0001_1100, 0b0000_0000];
+        assert_eq!(pbm, [&b"P4\n9 6\n"[..], row0, row0, row0, row1, row1, row1].concat());
+
+        let mut pgm = vec![];
+        grid.pgm(|&t| t as u8 * 50).write_to(&mut pgm).unwrap();
+        assert_eq!(pgm, [&b"P5\n3 2\n255\n"[..], &[0, 50, 100, 150, 200, 250]].concat());
+
+        let mut ppm = vec![];
+        let image = grid.ppm(|&t| [t as u8, 0, 255]).scale(2);
+        assert_eq!((image.width(), image.height()), (6, 4));
+        image.write_to(&mut ppm).unwrap();
+        assert_eq!(&ppm[.. 11], b"P6\n6 4\n255\n");
+        assert_eq!(ppm.len(), 11 + 6 * 4 * 3);
+        assert_eq!(&ppm[11 .. 17], &[0, 0, 255, 0, 0, 255]);
+        assert_eq!(&ppm[ppm.len() - 3 ..], &[5, 0, 255]);
+    }
+
+    #[test]
+    fn transforms() {
+        let grid: Grid<char> = vec!["ab".chars(), "cd".chars(), "ef".chars()].into_iter().collect();
+        assert_eq!(grid.rotate_cw().to_string(), "eca\nfdb\n");
+        assert_eq!(grid.flip_h().to_string(), "ba\ndc\nfe\n");
+        assert_eq!(grid.transpose().to_string(), "ace\nbdf\n");
+
+        let spun = grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!(spun, grid);
+        assert_eq!(grid.flip_h().flip_h(), grid);
+        assert_ne!(grid.transpose(), grid);
+    }
+
+    #[test]
+    fn views() {
+        let grid = Grid::new_with_mapping(4, 3, |x, y| x + 4 * y);
+        let view = grid.view((1, 1), (2, 2));
+        assert_eq!((view.width(), view.height(), view.origin()), (2, 2, (1, 1)));
+        assert_eq!(view[(1, 0)], 6);
+        assert_eq!(view.get((2, 0)), None);
+        assert_eq!(view.rows().collect::<Vec<_>>(), [&[5, 6], &[9, 10]]);
+        assert_eq!(view.elems().sum::<usize>(), 30);
+        assert_eq!(view.iter_coords().last(), Some(((1, 1), &10)));
+        assert_eq!(view.to_grid().elems().cloned().collect::<Vec<_>>(), [5, 6, 9, 10]);
+
+        let empty = grid.view((4, 3), (0, 0));
+        assert_eq!(empty.elems().count(), 0);
+
+        let sums: Vec<usize> = grid.windows(3, 2).map(|w| w.elems().sum()).collect();
+        assert_eq!(sums, [18, 24, 42, 48]);
+        assert_eq!(grid.windows(4, 3).count(), 1);
+        assert_eq!(grid.windows(5, 1).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn view_out_of_bounds() {
+        Grid::new(2, 2, 0).view((1, 1), (2, 1));
+    }
+
+    #[test]
+    fn hash() {
+        use std::collections::HashSet;
+
+        let a = Grid::new_with_mapping(2, 3, |x, y| x + y);
+        let b = Grid::new_with_mapping(3, 2, |x, y| x + y);
+        assert_ne!(a, b);
+
+        let seen: HashSet<Grid<usize>> = vec![a.clone(), b, a.transpose().transpose()].into_iter().collect();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.contains(&a));
+    }
+}
@@ -0,0 +1,182 @@
+//! Loading puzzle input.
+//!
+//! Each day reads its input from the file named by its first command-line
+//! argument, from standard input if that argument is `-`, or from
+//! `inputs/dayNN.txt` if it is missing.  Some days take their input inline,
+//! as the argument itself.
+//!
+//! Input is normalised as it is loaded: CRLF line endings become LF, and
+//! whitespace is trimmed from the end of each line.
+
+use std::env;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::str;
+
+/// Where to load input from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+    Inline(String),
+}
+
+impl Source {
+    /// The source named by the `n`th (1-based) command-line argument, for
+    /// day `day`.  If the argument is missing, the first input defaults to
+    /// `inputs/dayNN.txt`, and later ones to `inputs/dayNN-n.txt`.
+    pub fn arg(day: u32, n: usize) -> Source {
+        match env::args().nth(n) {
+            Some(ref arg) if arg == "-" => Source::Stdin,
+            Some(arg) => Source::File(PathBuf::from(arg)),
+            None => Source::File(default_path(day, n)),
+        }
+    }
+
+    /// As `arg`, but the argument itself (rather than the file it names) is
+    /// the input.
+    pub fn inline_arg(day: u32, n: usize) -> Source {
+        match env::args().nth(n) {
+            Some(ref arg) if arg == "-" => Source::Stdin,
+            Some(arg) => Source::Inline(arg),
+            None => Source::File(default_path(day, n)),
+        }
+    }
+
+    /// The name that errors in the input are attributed to.
+    pub fn name(&self) -> String {
+        match self {
+            Source::File(path) => path.display().to_string(),
+            Source::Stdin => "<stdin>".to_owned(),
+            Source::Inline(_) => "<argument>".to_owned(),
+        }
+    }
+
+    /// Loads the input.  Errors mention the source they came from, and how
+    /// `day` expects to be run.
+    pub fn load(self, day: u32) -> io::Result<Input> {
+        let name = self.name();
+        let context = |err: io::Error| io::Error::new(
+            err.kind(),
+            format!("{}: {} (usage: day{:02} [INPUT | -])", name, err, day),
+        );
+
+        let text = match self {
+            Source::File(path) => {
+                let mut text = String::new();
+                File::open(&path)
+                    .and_then(|mut file| file.read_to_string(&mut text))
+                    .map_err(context)?;
+                text
+            },
+
+            Source::Stdin => {
+                let mut text = String::new();
+                io::stdin().read_to_string(&mut text).map_err(context)?;
+                text
+            },
+
+            Source::Inline(text) => text,
+        };
+
+        Ok(Input::new(name, &text))
+    }
+}
+
+fn default_path(day: u32, n: usize) -> PathBuf {
+    if n <= 1 {
+        PathBuf::from(format!("inputs/day{:02}.txt", day))
+    } else {
+        PathBuf::from(format!("inputs/day{:02}-{}.txt", day, n))
+    }
+}
+
+/// Loads the input for day `day`, named by the first command-line argument.
+pub fn load(day: u32) -> io::Result<Input> {
+    Source::arg(day, 1).load(day)
+}
+
+/// Loads the input for day `day`, given inline by the first command-line
+/// argument.
+pub fn load_inline(day: u32) -> io::Result<Input> {
+    Source::inline_arg(day, 1).load(day)
+}
+
+/// Normalised puzzle input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Input {
+    /// Where the input came from, for attributing errors.
+    pub name: String,
+    text: String,
+}
+
+impl Input {
+    /// Input called `name`, consisting of `text` after normalisation.
+    pub fn new<N: Into<String>>(name: N, text: &str) -> Input {
+        let mut norm = String::with_capacity(text.len());
+        for line in text.lines() {
+            norm.push_str(line.trim_end());
+            norm.push('\n');
+        }
+
+        Input { name: name.into(), text: norm }
+    }
+
+    /// The whole input, with each line terminated by a newline.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+
+    pub fn lines(&self) -> str::Lines<'_> {
+        self.text.lines()
+    }
+
+    /// The first line of the input, for puzzles whose input is a single line.
+    pub fn first_line(&self) -> &str {
+        self.lines().next().unwrap_or("")
+    }
+
+    /// The input as a reader, e.g. for an `input!` type's `parse_all`.
+    pub fn reader(&self) -> &[u8] {
+        self.text.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use input::{Input, Source};
+    use std::io;
+    use std::path::PathBuf;
+
+    #[test]
+    fn normalise() {
+        let input = Input::new("x", "a  \r\n  b\t\n\r\nc");
+        assert_eq!(input.text(), "a\n  b\n\nc\n");
+        assert_eq!(input.lines().collect::<Vec<_>>(), ["a", "  b", "", "c"]);
+        assert_eq!(input.first_line(), "a");
+
+        let empty = Input::new("x", "");
+        assert_eq!(empty.text(), "");
+        assert_eq!(empty.first_line(), "");
+    }
+
+    #[test]
+    fn inline() {
+        let input = Source::Inline("  1234 ".to_owned()).load(14).unwrap();
+        assert_eq!(input.name, "<argument>");
+        assert_eq!(input.first_line(), "  1234");
+    }
+
+    #[test]
+    fn missing() {
+        let err = Source::File(PathBuf::from("no/such/file.txt")).load(3).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().starts_with("no/such/file.txt: "));
+        assert!(err.to_string().ends_with("(usage: day03 [INPUT | -])"));
+    }
+}
@@ -1,97 +1,589 @@
+pub mod automaton;
 pub mod chunkable;
+pub mod counter;
+pub mod cycle;
 pub mod grid;
+pub mod input;
+pub mod records;
+pub mod scan;
+pub mod search;
+pub mod sliding;
 
 #[macro_export] macro_rules! _parser_from_patt {
-    ($fun: ident, $cls:ident, $ctr:ident, $patt:expr, $suff:expr, $($field:ident: $ty:ty),*) => {
-        fn $fun(s: &str) -> std::io::Result<$cls> {
-            let ($($field),*) =
-                scan_fmt!(s, $patt, $($ty),*);
+    (@mode) => { $crate::scan::Mode::Loose };
+    (@mode $mode:ident) => { $crate::scan::Mode::$mode };
 
-            {
+    (@parse_all $vis:vis $cls:ident) => {
+        #[allow(dead_code)]
+        $vis fn parse_all<R: std::io::BufRead>(reader: R) -> $crate::records::ParseLines<R, $cls> {
+            $crate::records::ParseLines::new(reader, stringify!($cls))
+        }
+    };
+
+    (
+        $vis:vis $fun:ident, $cls:ident, $ctr:ident,
+        $patt:expr, $suff:expr, [$($mode:ident)*], $($field:ident: $ty:ty),*
+    ) => {
+        $vis fn $fun(s: &str) -> Result<$cls, $crate::scan::ParseError> {
+            use $crate::scan::{Field, ParseError};
+
+            // Check the pattern against the fields at compile time.
+            const _: () = {
+                use $crate::scan::{check_field, count_holes, Field, Mismatch};
+
+                let mut n = 0;
                 $(
-                    let $field = $field
-                        .ok_or_else(|| std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            concat!(
-                                stringify!($cls),
-                                ": parse failed on ",
-                                stringify!($field), "!")))?
-                );*;
-
-                if s.ends_with($suff) {
-                    Ok($ctr { $($field),* })
-                } else {
-                    Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidInput,
-                            concat!(
-                                stringify!($cls),
-                                ": parse failed on suffix!")))?
+                    match check_field($patt, n, <$ty as Field>::SHAPE) {
+                        Ok(()) => {},
+                        Err(Mismatch::Missing) => panic!(concat!(
+                            stringify!($cls), ".", stringify!($field),
+                            ": no placeholder for field in pattern")),
+                        Err(Mismatch::Arity) => panic!(concat!(
+                            stringify!($cls), ".", stringify!($field),
+                            ": placeholder modifier does not suit type ",
+                            stringify!($ty))),
+                        Err(Mismatch::NotInteger) => panic!(concat!(
+                            stringify!($cls), ".", stringify!($field),
+                            ": integer placeholder bound to non-integer type ",
+                            stringify!($ty))),
+                    }
+
+                    n += 1;
+                )*
+
+                if count_holes($patt) > n {
+                    panic!(concat!(
+                        stringify!($cls),
+                        ": more placeholders in pattern than fields"));
                 }
-            }
+            };
+
+            let fields: &[&'static str] = &[$(stringify!($field)),*];
+            let mode = _parser_from_patt!(@mode $($mode)*);
+            let scanned = $crate::scan::scan_line(s, $patt, $suff, mode)
+                .map_err(|f| ParseError::from_failure(stringify!($cls), fields, s, f))?;
+
+            let mut caps = scanned.caps.into_iter();
+
+            $(
+                let cap = caps.next().expect(concat!(
+                    stringify!($cls), ": no placeholder for ",
+                    stringify!($field), "!"));
+
+                let $field = <$ty as Field>::from_capture(cap)
+                    .map_err(|tok| ParseError::bad_value(
+                        stringify!($cls), stringify!($field), <$ty as Field>::SCALAR,
+                        s, tok.offset, tok.text))?;
+            )*
+
+            Ok($ctr { $($field),* })
         }
     }
 }
 
 /**
  * Defines a struct/enum that can be constructed from a string, based on a
- * format pattern.
+ * format pattern.  See the [`scan`] module for the pattern syntax.  Fields are
+ * bound to placeholders in order, and may be `Option`s (for `{d?}` style
+ * placeholders) or `Vec`s (for `{d*, }` style placeholders) of scalars.
+ *
+ * Patterns are checked against the fields at compile time: each field must
+ * have a placeholder whose modifier suits its type, and `{d}` placeholders
+ * must be bound to integer fields.  So each of these fails to compile:
+ *
+ * ```compile_fail
+ * # #[macro_use] extern crate lib;
+ * // `{d}` bound to a string.
+ * input! { #["{d}"; ""] struct Name { name: String } }
+ * # fn main() {}
+ * ```
+ *
+ * ```compile_fail
+ * # #[macro_use] extern crate lib;
+ * // `{d}` bound to a character.
+ * input! { #["{d}"; ""] struct Letter { c: char } }
+ * # fn main() {}
+ * ```
+ *
+ * ```compile_fail
+ * # #[macro_use] extern crate lib;
+ * // A placeholder with no field.
+ * input! { #["{d}, {d}"; ""] struct Coord { x: isize } }
+ * # fn main() {}
+ * ```
+ *
+ * ```compile_fail
+ * # #[macro_use] extern crate lib;
+ * // A repeated placeholder bound to an `Option`.
+ * input! { #["{d*, }"; ""] struct List { xs: Option<usize> } }
+ * # fn main() {}
+ * ```
+ *
+ * ```compile_fail
+ * # #[macro_use] extern crate lib;
+ * // A plain placeholder bound to a `Vec`.
+ * input! { #["{d}"; ""] struct List { xs: Vec<usize> } }
+ * # fn main() {}
+ * ```
+ *
+ * The type also implements `FromStr`, and `Display`, which writes values out
+ * following the pattern and suffix, so that they parse back to themselves.
+ * That holds as long as every field's value could have been matched by its
+ * placeholder in the first place: `String` and `char` values must not be
+ * empty or contain whitespace, nor contain text that would stop a `{}`
+ * placeholder early, i.e. that the literal or separator following it would
+ * match, if that starts with a non-whitespace character.  `Display` does not
+ * check this.
+ * Its `parse_all` function parses every line of a reader, returning a
+ * [`ParseLines`] iterator:
+ *
+ * ```ignore
+ * let rects = Rect::parse_all(reader).skip_blank().in_file(fname).all()?;
+ * ```
+ *
+ * By default lines are matched loosely, like `scanf`.  A [`Mode`] can follow
+ * the suffix in the pattern attribute to match more strictly, e.g.
+ * `#["#{d} @ {d},{d}: {d}x{d}"; ""; Strict]`.
+ *
+ * Visibility, doc comments and other outer attributes are passed through to
+ * the type, its fields and variants.  On structs and variants they follow the
+ * pattern attribute.  The type always derives `Debug`, `Clone`, `PartialEq`,
+ * `Eq` and `Hash`; further derives can be added with a `#[derive]` of their
+ * own.  `new` has the same visibility as the type:
+ *
+ * ```ignore
+ * input! {
+ *     #["{d}, {d}"; ""]
+ *     /// A point on the plane.
+ *     #[derive(Copy, PartialOrd, Ord)]
+ *     pub struct Coord { pub x: isize, pub y: isize }
+ * }
+ * ```
+ *
+ * [`scan`]: scan/index.html
+ * [`Mode`]: scan/enum.Mode.html
+ * [`ParseLines`]: records/struct.ParseLines.html
  */
 #[macro_export] macro_rules! input {
     (
-        #[$patt:expr; $suff:expr]
-        struct $name:ident {
-            $($field:ident: $ty:ty),*
+        #[$patt:expr; $suff:expr $(; $mode:ident)*]
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$fattr:meta])* $fvis:vis $field:ident: $ty:ty),* $(,)*
         }
     ) => {
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-        struct $name {
-            $($field: $ty),*
+        $(#[$attr])*
+        $vis struct $name {
+            $($(#[$fattr])* $fvis $field: $ty),*
         }
 
         impl $name {
-            _parser_from_patt! { new, $name, $name, $patt, $suff, $($field: $ty),* }
+            _parser_from_patt! { $vis new, $name, $name, $patt, $suff, [$($mode)*], $($field: $ty),* }
+            _parser_from_patt! { @parse_all $vis $name }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::scan::ParseError;
+
+            fn from_str(s: &str) -> Result<$name, $crate::scan::ParseError> {
+                $name::new(s)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let mut out = $crate::scan::Render::new(f, $patt);
+                $(out.field(&self.$field)?;)*
+                out.finish($suff)
+            }
         }
     };
 
     (
-        enum $name:ident {
+        $(#[$attr:meta])*
+        $vis:vis enum $name:ident {
             $(
-                #[$patt:expr; $suff:expr]
+                #[$patt:expr; $suff:expr $(; $mode:ident)*]
+                $(#[$vattr:meta])*
                 $label:ident {
-                    $($field:ident: $ty:ty),*
+                    $($(#[$fattr:meta])* $field:ident: $ty:ty),* $(,)*
                 }
-            ),*
+            ),* $(,)*
         }
     ) => {
 
         #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-        enum $name {
-            $($label { $($field: $ty),* }),*
+        $(#[$attr])*
+        $vis enum $name {
+            $($(#[$vattr])* $label { $($(#[$fattr])* $field: $ty),* }),*
         }
 
         impl $name {
-            fn new(s: &str) -> std::io::Result<$name> {
+            $vis fn new(s: &str) -> Result<$name, $crate::scan::ParseError> {
+                let mut failures = vec![];
                 $(
                     {
-                        use $name::$label;
+                        use self::$name::$label;
                         _parser_from_patt! {
                             _new_variant, $name, $label,
-                            $patt, $suff, $($field: $ty),*
+                            $patt, $suff, [$($mode)*], $($field: $ty),*
                         };
 
-                        let res = _new_variant(s);
-                        if res.is_ok() {
-                            return res;
+                        match _new_variant(s) {
+                            Ok(v) => return Ok(v),
+                            Err(e) => failures.push((stringify!($label), e)),
                         }
                     }
                 )*;
 
-                Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidInput,
-                        concat!(
-                            stringify!($name),
-                            ": parse failed on all variants!")))
+                Err($crate::scan::ParseError::no_variant(stringify!($name), s, failures))
+            }
+
+            _parser_from_patt! { @parse_all $vis $name }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::scan::ParseError;
+
+            fn from_str(s: &str) -> Result<$name, $crate::scan::ParseError> {
+                $name::new(s)
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                match self {
+                    $(
+                        $name::$label { $($field),* } => {
+                            let mut out = $crate::scan::Render::new(f, $patt);
+                            $(out.field($field)?;)*
+                            out.finish($suff)
+                        }
+                    ),*
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Defines a struct that can be parsed from a multi-line [`Record`], each of
+ * whose fields is parsed from one line of the record, in order.  Field types
+ * are usually declared with `input!`, but any type that implements `FromStr`
+ * with a `ParseError` will do.  For example:
+ *
+ * ```ignore
+ * record! {
+ *     struct Sample { before: Before, insn: Insn, after: After }
+ * }
+ *
+ * for rec in reader.lines().blocks() {
+ *     let sample: Sample = rec?.parse()?;
+ * }
+ * ```
+ *
+ * Records with too few or too many lines fail to parse.  Visibility and
+ * attributes are passed through as for `input!`, and the struct also
+ * implements `FromStr` and `Display`, with its lines separated by newlines.
+ *
+ * [`Record`]: records/struct.Record.html
+ */
+#[macro_export] macro_rules! record {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$fattr:meta])* $fvis:vis $field:ident: $ty:ty),* $(,)*
+        }
+    ) => {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        $(#[$attr])*
+        $vis struct $name {
+            $($(#[$fattr])* $fvis $field: $ty),*
+        }
+
+        impl $crate::records::FromRecord for $name {
+            fn from_record(
+                rec: &$crate::records::Record,
+            ) -> Result<$name, $crate::scan::ParseError> {
+                use $crate::scan::ParseError;
+
+                let mut lines = rec.lines.iter().enumerate();
+                $(
+                    let $field = match lines.next() {
+                        Some((i, line)) => line.parse::<$ty>()
+                            .map_err(|e: ParseError| e.on_line(rec.line + i))?,
+
+                        None => return Err(
+                            ParseError::missing_line(stringify!($name), stringify!($field))
+                                .on_line(rec.line + rec.lines.len())),
+                    };
+                )*
+
+                match lines.next() {
+                    Some((i, line)) => Err(
+                        ParseError::extra_line(stringify!($name), line).on_line(rec.line + i)),
+                    None => Ok($name { $($field),* }),
+                }
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = $crate::scan::ParseError;
+
+            fn from_str(s: &str) -> Result<$name, $crate::scan::ParseError> {
+                let lines = s.lines().map(str::to_owned).collect();
+                $crate::records::Record { line: 1, lines }.parse()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                let lines: &[&dyn std::fmt::Display] = &[$(&self.$field),*];
+                for (i, line) in lines.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+
+                    write!(f, "{}", line)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    input! {
+        #["{} -> {d*, }"; ""]
+        struct Edges {
+            from: String,
+            to: Vec<usize>
+        }
+    }
+
+    input! {
+        #["{d} {d?}"; "."]
+        struct Pair { a: isize, b: Option<isize> }
+    }
+
+    input! {
+        #["{*, } must go"; ""]
+        struct Chores { names: Vec<String> }
+    }
+
+    #[test]
+    fn repeated_field() {
+        assert_eq!(
+            Edges::new("a -> 1, 2, 3").unwrap(),
+            Edges { from: "a".to_owned(), to: vec![1, 2, 3] },
+        );
+
+        assert_eq!(
+            Edges::new("b ->").unwrap(),
+            Edges { from: "b".to_owned(), to: vec![] },
+        );
+    }
+
+    #[test]
+    fn optional_field() {
+        assert_eq!(Pair::new("1 2.").unwrap(), Pair { a: 1, b: Some(2) });
+        assert_eq!(Pair::new("-1.").unwrap(), Pair { a: -1, b: None });
+    }
+
+    input! {
+        enum Cmd {
+            #["{{{[a-z]}}} <- {d}"; ""]
+            Set { reg: char, val: usize },
+
+            #["{d*, }"; " pushed"]
+            Push { vals: Vec<usize> }
+        }
+    }
+
+    #[test]
+    fn display() {
+        let edges = Edges { from: "a".to_owned(), to: vec![1, 2, 3] };
+        assert_eq!(edges.to_string(), "a -> 1, 2, 3");
+        assert_eq!(Pair { a: 1, b: None }.to_string(), "1 .");
+        assert_eq!(Cmd::Set { reg: 'x', val: 4 }.to_string(), "{x} <- 4");
+        assert_eq!(Cmd::Push { vals: vec![1, 2] }.to_string(), "1, 2 pushed");
+    }
+
+    #[test]
+    fn round_trip() {
+        for a in -3 .. 3 {
+            for b in (-3 .. 3).map(Some).chain(None) {
+                let pair = Pair { a, b };
+                assert_eq!(pair.to_string().parse(), Ok(pair));
             }
         }
+
+        for n in 0 .. 4 {
+            let to: Vec<usize> = (0 .. n).map(|i| i * 10).collect();
+            let edges = Edges { from: format!("v{}", n), to };
+            assert_eq!(edges.to_string().parse(), Ok(edges));
+
+            let cmd = Cmd::Set { reg: (b'a' + n as u8) as char, val: n };
+            assert_eq!(cmd.to_string().parse(), Ok(cmd));
+
+            let cmd = Cmd::Push { vals: (0 .. n).collect() };
+            assert_eq!(cmd.to_string().parse(), Ok(cmd));
+        }
+
+        // Tokens containing some, but not all, of the text that follows them.
+        for from in &["a-b", "->", "a->b", "-", ">"] {
+            let edges = Edges { from: from.to_string(), to: vec![1, 2] };
+            assert_eq!(edges.to_string().parse(), Ok(edges));
+        }
+
+        let names = ["mum", "jump", "a-b", "must"];
+        let chores = Chores { names: names.iter().map(|n| n.to_string()).collect() };
+        assert_eq!(chores.to_string(), "mum, jump, a-b, must must go");
+        assert_eq!(chores.to_string().parse(), Ok(chores));
+
+        // An empty token is written as nothing, which no placeholder matches.
+        let edges = Edges { from: String::new(), to: vec![1] };
+        assert!(edges.to_string().parse::<Edges>().is_err());
+    }
+
+    #[test]
+    fn variant_failures() {
+        let err = "{x} <- y".parse::<Cmd>().unwrap_err();
+        assert_eq!(err.variant, Some("Set"));
+        assert_eq!(err.field, Some("val"));
+        assert_eq!(
+            err.to_string(),
+            "8: Cmd matched no variant, most likely Set.val expected integer, got \"y\"\n    \
+             column 8: Set.val expected integer, got \"y\"\n    \
+             column 1: Push expected suffix \" pushed\", got \"{x} <- y\"",
+        );
+    }
+
+    mod shared {
+        input! {
+            #["{d}, {d}"; ""]
+            /// A point on the plane, ordered by row.
+            #[derive(Copy, PartialOrd, Ord)]
+            pub struct Coord {
+                /// Row, compared first.
+                pub y: isize,
+                pub x: isize,
+            }
+        }
+
+        input! {
+            /// A machine instruction.
+            #[derive(Copy)]
+            pub(crate) enum Op {
+                #["inc {[a-z]}"; ""]
+                /// Increments a register.
+                Inc { reg: char },
+
+                #["jmp {d}"; ""]
+                Jmp { by: isize },
+            }
+        }
+    }
+
+    #[test]
+    fn visibility_and_derives() {
+        use self::shared::{Coord, Op};
+
+        let mut coords: Vec<Coord> = ["3, 1", "1, 2", "1, 1"].iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+
+        coords.sort();
+        assert_eq!(coords, vec![
+            Coord { y: 1, x: 1 },
+            Coord { y: 1, x: 2 },
+            Coord { y: 3, x: 1 },
+        ]);
+
+        let op = Op::new("jmp -2").unwrap();
+        let copy = op;
+        assert_eq!(op, copy);
+        assert_eq!(Op::new("inc a").unwrap(), Op::Inc { reg: 'a' });
+    }
+
+    input! {
+        #["#{d} @ {d},{d}"; ""; Strict]
+        struct Claim { id: usize, left: usize, top: usize }
+    }
+
+    input! {
+        #["{d} {d?}"; "."; Lenient]
+        struct Spaced { a: isize, b: Option<isize> }
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(Claim::new("#1 @ 2,3"), Ok(Claim { id: 1, left: 2, top: 3 }));
+        assert_eq!(
+            Claim::new("#1 @ 2,3 4x4").unwrap_err().to_string(),
+            "9: Claim expected end of line, got \" 4x4\"",
+        );
+
+        assert_eq!(Spaced::new(" 1   2.\r"), Ok(Spaced { a: 1, b: Some(2) }));
+        assert_eq!(Spaced::new("1 ."), Ok(Spaced { a: 1, b: None }));
+        assert!(Spaced::new("1 2. 3").is_err());
+    }
+
+    record! {
+        /// A pair, followed by the edges out of it.
+        struct Both { pair: Pair, edges: Edges }
+    }
+
+    #[test]
+    fn records() {
+        let both: Both = "1 2.\na -> 3, 4".parse().unwrap();
+        assert_eq!(both.pair, Pair { a: 1, b: Some(2) });
+        assert_eq!(both.edges.to, vec![3, 4]);
+        assert_eq!(both.to_string().parse(), Ok(both));
+
+        let err = |s: &str| s.parse::<Both>().unwrap_err().to_string();
+        assert_eq!(err("1 2.\nx ->\n3 ."), "3:1: Both expected end of record, got \"3 .\"");
+        assert_eq!(err("1 2."), "2:1: Both.edges expected a line, got end of record");
+        assert_eq!(err("1 2.\nx"), "2:2: Edges expected \"->\", got end of line");
+    }
+
+    #[test]
+    fn parse_all() {
+        let text = "1 2.\n\n  # comment\n3 .\n";
+        let pairs = Pair::parse_all(text.as_bytes())
+            .skip_blank()
+            .skip_comments("#")
+            .all();
+
+        assert_eq!(pairs, Ok(vec![Pair { a: 1, b: Some(2) }, Pair { a: 3, b: None }]));
+
+        let err = Pair::parse_all(text.as_bytes()).in_file("pairs.txt").all().unwrap_err();
+        assert_eq!(err.to_string(), "pairs.txt:2:1: Pair.a expected integer, got end of line");
+
+        let errs = Cmd::parse_all("1 pushed\nx\n{a} <- 1\ny".as_bytes())
+            .all_errors()
+            .unwrap_err();
+
+        assert_eq!(errs.0.iter().map(|e| e.line).collect::<Vec<_>>(), [Some(2), Some(4)]);
+
+        let bad = Pair::parse_all(&b"1 .\n\xff .\n2 ."[..]).all_errors().unwrap_err();
+        assert_eq!(
+            bad.to_string(),
+            "2:1: Pair could not be read: stream did not contain valid UTF-8",
+        );
+    }
+
+    #[test]
+    fn failures() {
+        let err = |s| Pair::new(s).unwrap_err().to_string();
+        assert_eq!(err("x 2."), "1: Pair.a expected integer, got \"x\"");
+        assert_eq!(err("1 2"), "4: Pair expected suffix \".\", got end of line");
+        assert_eq!(
+            err("1 99999999999999999999."),
+            "3: Pair.b expected isize, got \"99999999999999999999\"",
+        );
     }
 }
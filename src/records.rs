@@ -0,0 +1,282 @@
+//! Reading input made up of records: single lines, or groups of lines such as
+//! those separated by blank lines.
+//!
+//! [`ParseLines`] parses each line of a reader into a value, as returned by
+//! the `parse_all` function of types declared by `input!`.
+//!
+//! [`ReadRecords`] splits a stream of lines into [`Record`]s, which can then
+//! be parsed into any type implementing [`FromRecord`], such as those declared
+//! by the `record!` macro.
+//!
+//! [`ParseLines`]: struct.ParseLines.html
+//! [`ReadRecords`]: trait.ReadRecords.html
+//! [`Record`]: struct.Record.html
+//! [`FromRecord`]: trait.FromRecord.html
+
+use scan::{ParseError, ParseErrors};
+use std::io::{self, BufRead};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+/// An iterator that parses each line of a reader into a `T`, attributing any
+/// errors to the line (and optionally file) they occurred on.
+pub struct ParseLines<R, T> {
+    lines: io::Lines<R>,
+    /// Name of the type being parsed, for reporting I/O errors.
+    ty: &'static str,
+    /// Number of lines consumed so far.
+    line: usize,
+    skip_blank: bool,
+    comment: Option<String>,
+    file: Option<String>,
+    /// Set after an I/O error that the reader may not recover from.
+    failed: bool,
+    parsed: PhantomData<fn() -> T>,
+}
+
+impl <R, T> ParseLines<R, T>
+    where R: BufRead,
+          T: FromStr<Err = ParseError>
+{
+    /// Parses the lines of `reader` into values of `T`, a type called `ty`.
+    pub fn new(reader: R, ty: &'static str) -> ParseLines<R, T> {
+        ParseLines {
+            lines: reader.lines(),
+            ty,
+            line: 0,
+            skip_blank: false,
+            comment: None,
+            file: None,
+            failed: false,
+            parsed: PhantomData,
+        }
+    }
+
+    /// Skips lines that are empty or only contain whitespace.
+    pub fn skip_blank(self) -> ParseLines<R, T> {
+        ParseLines { skip_blank: true, ..self }
+    }
+
+    /// Skips lines whose first non-whitespace characters are `prefix`.
+    pub fn skip_comments(self, prefix: &str) -> ParseLines<R, T> {
+        ParseLines { comment: Some(prefix.to_owned()), ..self }
+    }
+
+    /// Attributes errors to the file called `file`.
+    pub fn in_file<F: Into<String>>(self, file: F) -> ParseLines<R, T> {
+        ParseLines { file: Some(file.into()), ..self }
+    }
+
+    /// Parses every line, stopping at the first error.
+    pub fn all(self) -> Result<Vec<T>, ParseError> {
+        self.collect()
+    }
+
+    /// Parses every line, returning all the errors if any line fails.
+    pub fn all_errors(self) -> Result<Vec<T>, ParseErrors> {
+        let mut vals = vec![];
+        let mut errs = vec![];
+        for res in self {
+            match res {
+                Ok(val) => vals.push(val),
+                Err(err) => errs.push(err),
+            }
+        }
+
+        if errs.is_empty() {
+            Ok(vals)
+        } else {
+            Err(ParseErrors(errs))
+        }
+    }
+
+    fn is_skipped(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        (self.skip_blank && line.is_empty())
+            || self.comment.as_ref().is_some_and(|prefix| line.starts_with(&**prefix))
+    }
+
+    fn locate(&self, err: ParseError) -> ParseError {
+        let err = err.on_line(self.line);
+        match &self.file {
+            Some(file) => err.in_file(&**file),
+            None => err,
+        }
+    }
+}
+
+impl <R, T> Iterator for ParseLines<R, T>
+    where R: BufRead,
+          T: FromStr<Err = ParseError>
+{
+    type Item = Result<T, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            let res = self.lines.next()?;
+            self.line += 1;
+
+            let line = match res {
+                Ok(line) => line,
+                Err(err) => {
+                    // Lines that are not valid UTF-8 are skipped over, but
+                    // other errors end the iteration.
+                    self.failed = err.kind() != io::ErrorKind::InvalidData;
+                    return Some(Err(self.locate(ParseError::io(self.ty, &err))));
+                },
+            };
+
+            if !self.is_skipped(&line) {
+                return Some(line.parse().map_err(|err| self.locate(err)));
+            }
+        }
+
+        None
+    }
+}
+
+/// A group of consecutive lines of input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// The (1-based) number of the record's first line.
+    pub line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Record {
+    /// Parses the record into a `T`.  Errors are attributed to the line they
+    /// occurred on.
+    pub fn parse<T: FromRecord>(&self) -> Result<T, ParseError> {
+        T::from_record(self)
+    }
+}
+
+/// Types that can be parsed from a multi-line record.
+pub trait FromRecord: Sized {
+    fn from_record(rec: &Record) -> Result<Self, ParseError>;
+}
+
+/// An iterator over the records in a stream of lines.
+///
+/// Constructed by a call to [`blocks`] or [`groups`].
+///
+/// [`blocks`]: trait.ReadRecords.html#method.blocks
+/// [`groups`]: trait.ReadRecords.html#method.groups
+pub struct Records<I> {
+    lines: I,
+    /// Number of lines consumed so far.
+    line: usize,
+    /// Number of lines in each record, or `None` if records are separated by
+    /// blank lines.
+    size: Option<usize>,
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+impl <I> Iterator for Records<I>
+    where I: Iterator<Item = io::Result<String>>
+{
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut rec = Record { line: 0, lines: vec![] };
+
+        while self.size.is_none_or(|size| rec.lines.len() < size) {
+            let line = match self.lines.next() {
+                Some(Ok(line)) => line,
+                Some(Err(e)) => return Some(Err(e)),
+                None => break,
+            };
+
+            self.line += 1;
+            if is_blank(&line) {
+                if rec.lines.is_empty() {
+                    continue;
+                } else if self.size.is_none() {
+                    break;
+                }
+            }
+
+            if rec.lines.is_empty() {
+                rec.line = self.line;
+            }
+
+            rec.lines.push(line);
+        }
+
+        if rec.lines.is_empty() {
+            None
+        } else {
+            Some(Ok(rec))
+        }
+    }
+}
+
+/// Extension trait for iterators over lines of input, that groups them into
+/// records.
+pub trait ReadRecords
+    where Self: Iterator<Item = io::Result<String>> + Sized
+{
+    /// Creates an iterator over runs of non-blank lines, separated by one or
+    /// more blank lines.
+    fn blocks(self) -> Records<Self> {
+        Records { lines: self, line: 0, size: None }
+    }
+
+    /// Creates an iterator over groups of `size` consecutive lines.  Blank
+    /// lines before the start of a group are skipped, but once a group has
+    /// started, blank lines count towards its `size` and are kept.  The last
+    /// group may be short if the input runs out.
+    fn groups(self, size: usize) -> Records<Self> {
+        assert!(size > 0, "Records must have at least one line");
+        Records { lines: self, line: 0, size: Some(size) }
+    }
+}
+
+/// All iterators over lines can be read as records.
+impl <I> ReadRecords for I where I: Iterator<Item = io::Result<String>> {}
+
+#[cfg(test)]
+mod tests {
+    use records::{ReadRecords, Record};
+    use std::io::{self, BufRead};
+
+    fn records(text: &str, size: Option<usize>) -> Vec<Record> {
+        let lines = io::Cursor::new(text).lines();
+        let recs = match size {
+            Some(size) => lines.groups(size),
+            None => lines.blocks(),
+        };
+
+        recs.collect::<io::Result<_>>().unwrap()
+    }
+
+    fn rec(line: usize, lines: &[&str]) -> Record {
+        Record { line, lines: lines.iter().map(|l| l.to_string()).collect() }
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(
+            records("\na\nb\n\n\n  \nc\n\n", None),
+            vec![rec(2, &["a", "b"]), rec(7, &["c"])],
+        );
+
+        assert_eq!(records("\n\n", None), vec![]);
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(
+            records("a\nb\nc\n\nd\n\ne\nf", Some(2)),
+            vec![rec(1, &["a", "b"]), rec(3, &["c", ""]), rec(5, &["d", ""]), rec(7, &["e", "f"])],
+        );
+
+        assert_eq!(
+            records("a\nb\n\n\nc", Some(2)),
+            vec![rec(1, &["a", "b"]), rec(5, &["c"])],
+        );
+    }
+}
@@ -0,0 +1,1329 @@
+//! A small `scanf`-style matcher, used by the `input!` macro to pull fields
+//! out of a line of input.
+//!
+//! Patterns are literal text interspersed with placeholders:
+//!
+//!  - `{d}` matches a base-10 integer, with an optional sign.
+//!  - `{}` matches a run of non-whitespace characters, stopping early at the
+//!    first point where the literal text that follows it matches, unless that
+//!    text starts with whitespace.  Elements of a repeated `{}` also stop
+//!    where their separator matches, under the same condition.
+//!  - `{[...]}` matches a run of characters from a set, e.g. `{[.#]}`.  A
+//!    leading `^` inverts the set, and `a-z` denotes a range.
+//!  - `{{` and `}}` match a literal `{` and `}` respectively.
+//!
+//! A placeholder may be followed by a modifier:
+//!
+//!  - `{d?}` is optional, and binds to an `Option<T>` field, which is `None`
+//!    if the placeholder does not match.
+//!  - `{d*, }` is repeated zero or more times, and binds to a `Vec<T>` field.
+//!    The text between the `*` and the closing brace separates consecutive
+//!    elements.  If it is empty (`{d*}`), elements are separated by
+//!    whitespace.
+//!
+//! By default, like `scanf`, whitespace is insignificant: whitespace in the
+//! pattern matches any amount (including none) of whitespace in the input,
+//! and whitespace in the input is skipped before each placeholder and literal.
+//! Lines that need to be checked more carefully can be matched in a stricter
+//! [`Mode`].
+//!
+//! [`Mode`]: enum.Mode.html
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::ops::Deref;
+
+/// A range of bytes in a pattern.
+type Span = (usize, usize);
+
+/// Pattern segments refer to text in the pattern either by `Span` (while
+/// parsing, which happens in `const` contexts) or by `&str`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind<S> { Decimal, Token, Class(S) }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rep<S> { One, Maybe, Many(S) }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Segment<S> {
+    Lit(S),
+    Hole(Kind<S>, Rep<S>),
+}
+
+/// How closely a line must follow its pattern and suffix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Like `scanf`: whitespace is insignificant, and the line need only end
+    /// with the suffix, so anything between the pattern and suffix is
+    /// ignored.
+    Loose,
+    /// The line must consist of exactly the pattern followed by the suffix,
+    /// whitespace included.
+    Strict,
+    /// As `Strict`, but a run of whitespace in the pattern or suffix matches
+    /// any amount of whitespace in the line, and whitespace at either end of
+    /// the line (such as the `\r` of a CRLF line ending) is ignored.
+    Lenient,
+}
+
+/// A run of text matched by a placeholder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'s> {
+    pub text: &'s str,
+    /// Byte offset of `text` into the input.
+    pub offset: usize,
+}
+
+/// The text matched by a single placeholder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Capture<'s> {
+    One(Token<'s>),
+    Maybe(Option<Token<'s>>),
+    Many(Vec<Token<'s>>),
+}
+
+/// The result of successfully matching a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match<'s> {
+    /// The text captured by each placeholder, in order.
+    pub caps: Vec<Capture<'s>>,
+    /// Byte offset into the input just after the matched text.
+    pub end: usize,
+}
+
+/// What the input was expected to contain, at the point it failed to parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expected {
+    /// A `{d}` placeholder.
+    Integer,
+    /// A `{}` placeholder.
+    Token,
+    /// A `{[...]}` placeholder, with the given character set.
+    Class(String),
+    /// Literal text from the pattern.
+    Literal(String),
+    /// The suffix that the line should end with.
+    Suffix(String),
+    /// The end of the line, after the suffix.
+    End,
+    /// Another line of a multi-line record.
+    Line,
+    /// The end of a multi-line record.
+    RecordEnd,
+    /// A line that could be read, rather than the I/O error described.
+    Io(String),
+    /// Text that could be converted into a value of the named type.
+    Value(&'static str),
+    /// Text matching one of an enum's variants.
+    Variant,
+}
+
+/// Describes where a scan stopped matching.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    /// Index of the placeholder being matched (or the next placeholder, if
+    /// the failure was in literal text).  Equal to the number of
+    /// placeholders if the failure happened after the last of them.
+    pub hole: usize,
+    /// Byte offset into the input at which matching failed.
+    pub offset: usize,
+    pub expected: Expected,
+}
+
+/// Tests whether `patt` has byte `b` at position `i`.
+const fn is_at(patt: &[u8], i: usize, b: u8) -> bool {
+    i < patt.len() && patt[i] == b
+}
+
+/// Parses the segment of `patt` starting at byte `pos`, returning it along
+/// with the position just after it.  This is a `const fn` so that `input!`
+/// can check its patterns at compile time.
+///
+/// Panics if the pattern is malformed.
+const fn next_segment(patt: &[u8], pos: usize) -> (Segment<Span>, usize) {
+    let len = patt.len();
+
+    if is_at(patt, pos, b'{') && is_at(patt, pos + 1, b'{') {
+        return (Segment::Lit((pos, pos + 1)), pos + 2);
+    }
+
+    if is_at(patt, pos, b'}') {
+        if is_at(patt, pos + 1, b'}') {
+            return (Segment::Lit((pos, pos + 1)), pos + 2);
+        }
+
+        panic!("Unbalanced closing brace in pattern");
+    }
+
+    if !is_at(patt, pos, b'{') {
+        let mut end = pos;
+        while end < len && patt[end] != b'{' && patt[end] != b'}' {
+            end += 1;
+        }
+
+        return (Segment::Lit((pos, end)), end);
+    }
+
+    let mut i = pos + 1;
+    let kind = if is_at(patt, i, b'd') {
+        i += 1;
+        Kind::Decimal
+    } else if is_at(patt, i, b'[') {
+        // A `]` straight after the opening bracket is part of the set.
+        let body = i + 1;
+        let mut close = body;
+        if is_at(patt, close, b'^') && is_at(patt, close + 1, b']') {
+            close += 2;
+        } else if is_at(patt, close, b']') {
+            close += 1;
+        }
+
+        while close < len && patt[close] != b']' {
+            close += 1;
+        }
+
+        if close == len {
+            panic!("Unterminated character class in pattern");
+        }
+
+        i = close + 1;
+        Kind::Class((body, close))
+    } else {
+        Kind::Token
+    };
+
+    let rep = if is_at(patt, i, b'?') {
+        i += 1;
+        Rep::Maybe
+    } else if is_at(patt, i, b'*') {
+        let sep = i + 1;
+        i = sep;
+        while i < len && patt[i] != b'}' {
+            i += 1;
+        }
+
+        Rep::Many((sep, i))
+    } else {
+        Rep::One
+    };
+
+    if !is_at(patt, i, b'}') {
+        panic!("Malformed placeholder in pattern");
+    }
+
+    (Segment::Hole(kind, rep), i + 1)
+}
+
+/// Splits `patt` into its literal and placeholder segments.
+///
+/// Panics if the pattern is malformed.
+fn segments(patt: &str) -> Vec<Segment<&str>> {
+    let text = |(start, end): Span| &patt[start..end];
+
+    let mut segs = vec![];
+    let mut pos = 0;
+    while pos < patt.len() {
+        let (seg, next) = next_segment(patt.as_bytes(), pos);
+        segs.push(match seg {
+            Segment::Lit(lit) => Segment::Lit(text(lit)),
+            Segment::Hole(kind, rep) => Segment::Hole(
+                match kind {
+                    Kind::Decimal => Kind::Decimal,
+                    Kind::Token => Kind::Token,
+                    Kind::Class(set) => Kind::Class(text(set)),
+                },
+                match rep {
+                    Rep::One => Rep::One,
+                    Rep::Maybe => Rep::Maybe,
+                    Rep::Many(sep) => Rep::Many(text(sep)),
+                },
+            ),
+        });
+
+        pos = next;
+    }
+
+    segs
+}
+
+/// How many values a field holds, corresponding to a placeholder's modifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity { One, Maybe, Many }
+
+/// Broad categories of scalar, for checking that placeholders suit the
+/// fields they are bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category { Integer, Other }
+
+/// Describes the placeholders a field can be bound to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Shape {
+    pub arity: Arity,
+    pub category: Category,
+}
+
+/// Reasons a placeholder cannot be bound to a field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mismatch {
+    /// The pattern has no placeholder for the field.
+    Missing,
+    /// The placeholder's modifier does not suit the field's arity.
+    Arity,
+    /// A `{d}` placeholder bound to a non-integer field.
+    NotInteger,
+}
+
+/// Counts the placeholders in `patt`.
+///
+/// Panics if the pattern is malformed.
+pub const fn count_holes(patt: &str) -> usize {
+    let patt = patt.as_bytes();
+    let mut holes = 0;
+    let mut pos = 0;
+    while pos < patt.len() {
+        let (seg, next) = next_segment(patt, pos);
+        if let Segment::Hole(..) = seg {
+            holes += 1;
+        }
+
+        pos = next;
+    }
+
+    holes
+}
+
+/// Checks that the `n`th (0-based) placeholder in `patt` can be bound to a
+/// field of shape `field`.  Used by `input!` to check its patterns at
+/// compile time.
+///
+/// Panics if the pattern is malformed.
+pub const fn check_field(patt: &str, n: usize, field: Shape) -> Result<(), Mismatch> {
+    let patt = patt.as_bytes();
+    let mut holes = 0;
+    let mut pos = 0;
+    while pos < patt.len() {
+        let (seg, next) = next_segment(patt, pos);
+        pos = next;
+
+        let (kind, rep) = match seg {
+            Segment::Hole(kind, rep) => (kind, rep),
+            Segment::Lit(_) => continue,
+        };
+
+        if holes < n {
+            holes += 1;
+            continue;
+        }
+
+        let arity_ok = matches!(
+            (rep, field.arity),
+            (Rep::One, Arity::One) | (Rep::Maybe, Arity::Maybe) | (Rep::Many(_), Arity::Many)
+        );
+
+        return if !arity_ok {
+            Err(Mismatch::Arity)
+        } else if let (Kind::Decimal, Category::Other) = (kind, field.category) {
+            Err(Mismatch::NotInteger)
+        } else {
+            Ok(())
+        };
+    }
+
+    Err(Mismatch::Missing)
+}
+
+/// Tests whether `c` belongs to the character class described by `set`.
+fn in_class(set: &str, c: char) -> bool {
+    let (invert, set) = match set.strip_prefix('^') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, set),
+    };
+
+    let chars: Vec<char> = set.chars().collect();
+    let mut found = false;
+    let mut i = 0;
+    while i < chars.len() {
+        if i + 2 < chars.len() && chars[i + 1] == '-' {
+            found |= chars[i] <= c && c <= chars[i + 2];
+            i += 3;
+        } else {
+            found |= chars[i] == c;
+            i += 1;
+        }
+    }
+
+    found != invert
+}
+
+/// Whether a token followed by `lit` should stop where `lit` matches.  A
+/// token never contains whitespace, so it already stops in time for a `lit`
+/// that starts with it.
+fn stops_token(lit: &str) -> bool {
+    lit.chars().next().is_some_and(|c| !c.is_whitespace())
+}
+
+struct Scanner<'s> {
+    input: &'s str,
+    pos: usize,
+    mode: Mode,
+}
+
+impl <'s> Scanner<'s> {
+    fn rest(&self) -> &'s str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn literal(&mut self, lit: &str) -> Result<(), Expected> {
+        for (i, c) in lit.char_indices() {
+            match self.mode {
+                Mode::Loose => self.skip_whitespace(),
+                Mode::Lenient if c.is_whitespace() => self.skip_whitespace(),
+                _ => {},
+            }
+
+            if c.is_whitespace() && self.mode != Mode::Strict {
+                continue;
+            }
+
+            if !self.rest().starts_with(c) {
+                let lit = match self.mode {
+                    Mode::Loose => lit[i..].trim_end(),
+                    _ => &lit[i..],
+                };
+
+                return Err(Expected::Literal(lit.to_owned()));
+            }
+
+            self.pos += c.len_utf8();
+        }
+
+        Ok(())
+    }
+
+    /// Tests whether `lit` matches at byte offset `pos`, without consuming it.
+    fn matches_at(&self, pos: usize, lit: &str) -> bool {
+        Scanner { pos, ..*self }.literal(lit).is_ok()
+    }
+
+    /// Matches a single occurrence of a placeholder of the given `kind`.
+    /// Tokens additionally stop where any literal in `stops` matches.
+    fn atom(&mut self, kind: Kind<&str>, stops: &[&str]) -> Result<Token<'s>, Expected> {
+        if self.mode == Mode::Loose {
+            self.skip_whitespace();
+        }
+
+        let rest = self.rest();
+
+        let len = match kind {
+            Kind::Decimal => {
+                let sign = if rest.starts_with(['+', '-']) { 1 } else { 0 };
+                let digits = rest[sign..].bytes()
+                    .take_while(u8::is_ascii_digit)
+                    .count();
+
+                if digits == 0 { 0 } else { sign + digits }
+            },
+
+            Kind::Token => rest
+                .char_indices()
+                .find(|&(i, c)| {
+                    c.is_whitespace() || stops.iter().any(|lit| self.matches_at(self.pos + i, lit))
+                })
+                .map_or(rest.len(), |(i, _)| i),
+
+            Kind::Class(set) => rest
+                .find(|c| !in_class(set, c))
+                .unwrap_or(rest.len()),
+        };
+
+        if len == 0 {
+            return Err(match kind {
+                Kind::Decimal => Expected::Integer,
+                Kind::Token => Expected::Token,
+                Kind::Class(set) => Expected::Class(set.to_owned()),
+            });
+        }
+
+        let offset = self.pos;
+        self.pos += len;
+        Ok(Token { text: &rest[..len], offset })
+    }
+
+    fn hole(&mut self, kind: Kind<&str>, rep: Rep<&str>, stops: &[&str]) -> Result<Capture<'s>, Expected> {
+        match rep {
+            Rep::One => self.atom(kind, stops).map(Capture::One),
+
+            Rep::Maybe => {
+                let save = self.pos;
+                let atom = self.atom(kind, stops).ok();
+                if atom.is_none() {
+                    self.pos = save;
+                }
+
+                Ok(Capture::Maybe(atom))
+            },
+
+            Rep::Many(sep) => {
+                let sep = if sep.is_empty() { " " } else { sep };
+                let mut stops = stops.to_vec();
+                if stops_token(sep) {
+                    stops.push(sep);
+                }
+
+                let mut items = vec![];
+                let mut save = self.pos;
+                while items.is_empty() || self.literal(sep).is_ok() {
+                    match self.atom(kind, &stops) {
+                        Ok(item) => items.push(item),
+                        Err(_) => break,
+                    }
+
+                    save = self.pos;
+                }
+
+                // Give back any separator or whitespace consumed after the
+                // last element.
+                self.pos = save;
+                Ok(Capture::Many(items))
+            },
+        }
+    }
+
+    /// Matches the segments of `patt` in turn, returning the text captured by
+    /// each of its placeholders.
+    fn pattern(&mut self, patt: &str) -> Result<Vec<Capture<'s>>, Failure> {
+        let segs = segments(patt);
+        let mut caps = vec![];
+
+        for (i, seg) in segs.iter().enumerate() {
+            let res = match *seg {
+                Segment::Lit(lit) => self.literal(lit),
+
+                Segment::Hole(kind, rep) => {
+                    let stops = match segs.get(i + 1) {
+                        Some(&Segment::Lit(next)) if stops_token(next) => vec![next],
+                        _ => vec![],
+                    };
+
+                    self.hole(kind, rep, &stops).map(|cap| caps.push(cap))
+                },
+            };
+
+            if let Err(expected) = res {
+                return Err(Failure { hole: caps.len(), offset: self.pos, expected });
+            }
+        }
+
+        Ok(caps)
+    }
+}
+
+/// Matches `input` against `patt`, returning the text captured by each of
+/// its placeholders, and where the match ended.  Any input left over after
+/// the pattern has been matched is ignored.
+///
+/// Panics if `patt` is malformed.
+pub fn scan<'s>(input: &'s str, patt: &str) -> Result<Match<'s>, Failure> {
+    let mut scanner = Scanner { input, pos: 0, mode: Mode::Loose };
+    let caps = scanner.pattern(patt)?;
+    Ok(Match { caps, end: scanner.pos })
+}
+
+/// Matches a whole line, `input`, against `patt` followed by `suffix`,
+/// following the rules of `mode`.
+///
+/// Panics if `patt` is malformed.
+pub fn scan_line<'s>(input: &'s str, patt: &str, suffix: &str, mode: Mode) -> Result<Match<'s>, Failure> {
+    let mut scanner = Scanner { input, pos: 0, mode };
+    if mode == Mode::Lenient {
+        scanner.skip_whitespace();
+    }
+
+    let caps = scanner.pattern(patt)?;
+    let end = scanner.pos;
+    let fail = |offset, expected| Err(Failure { hole: caps.len(), offset, expected });
+
+    if mode == Mode::Loose {
+        if !input.ends_with(suffix) {
+            return fail(end, Expected::Suffix(suffix.to_owned()));
+        }
+    } else {
+        if scanner.literal(suffix).is_err() {
+            return fail(end, Expected::Suffix(suffix.to_owned()));
+        }
+
+        if mode == Mode::Lenient {
+            scanner.skip_whitespace();
+        }
+
+        if scanner.pos < input.len() {
+            return fail(scanner.pos, Expected::End);
+        }
+    }
+
+    Ok(Match { caps, end })
+}
+
+/// Types that can be parsed from the text matched by a single placeholder,
+/// and written back out for it.
+pub trait Scalar: Sized + fmt::Display {
+    /// Name of the type, for error messages.
+    const NAME: &'static str;
+
+    /// Whether the type can be bound to a `{d}` placeholder.
+    const CATEGORY: Category = Category::Other;
+
+    fn from_token(tok: &str) -> Option<Self>;
+}
+
+macro_rules! scalar_from_str {
+    ($category:expr; $($ty:ty),*) => {
+        $(
+            impl Scalar for $ty {
+                const NAME: &'static str = stringify!($ty);
+                const CATEGORY: Category = $category;
+
+                fn from_token(tok: &str) -> Option<$ty> {
+                    tok.parse().ok()
+                }
+            }
+        )*
+    }
+}
+
+scalar_from_str! {
+    Category::Integer;
+    u8, u16, u32, u64, usize,
+    i8, i16, i32, i64, isize
+}
+
+scalar_from_str! { Category::Other; bool, char, String }
+
+/// Types that can be bound to a placeholder in an `input!` pattern: scalars
+/// bind to plain placeholders, `Option`s to optional ones, and `Vec`s to
+/// repeated ones.
+pub trait Field: Sized {
+    /// Name of the scalar type being parsed, for error messages.
+    const SCALAR: &'static str;
+
+    /// The placeholders this field can be bound to.
+    const SHAPE: Shape;
+
+    /// Converts a capture into a field value, returning the offending token
+    /// on failure.
+    ///
+    /// Panics if the capture's shape does not match the field's type (e.g. a
+    /// repeated placeholder bound to a scalar field).  `input!` rules this
+    /// out at compile time.
+    fn from_capture(cap: Capture<'_>) -> Result<Self, Token<'_>>;
+
+    /// Writes the field out as it would appear in the input, using `sep` to
+    /// separate repeated elements.
+    fn render(&self, f: &mut fmt::Formatter, sep: &str) -> fmt::Result;
+}
+
+impl <T> Field for T where T: Scalar {
+    const SCALAR: &'static str = T::NAME;
+    const SHAPE: Shape = Shape { arity: Arity::One, category: T::CATEGORY };
+
+    fn from_capture(cap: Capture<'_>) -> Result<T, Token<'_>> {
+        match cap {
+            Capture::One(tok) => T::from_token(tok.text).ok_or(tok),
+            _ => panic!("Scalar field bound to an optional or repeated placeholder"),
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter, _sep: &str) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl <T> Field for Option<T> where T: Scalar {
+    const SCALAR: &'static str = T::NAME;
+    const SHAPE: Shape = Shape { arity: Arity::Maybe, category: T::CATEGORY };
+
+    fn from_capture(cap: Capture<'_>) -> Result<Option<T>, Token<'_>> {
+        match cap {
+            Capture::Maybe(None) => Ok(None),
+            Capture::Maybe(Some(tok)) => T::from_token(tok.text).map(Some).ok_or(tok),
+            _ => panic!("Option field bound to a non-optional placeholder"),
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter, _sep: &str) -> fmt::Result {
+        match self {
+            Some(val) => write!(f, "{}", val),
+            None => Ok(()),
+        }
+    }
+}
+
+impl <T> Field for Vec<T> where T: Scalar {
+    const SCALAR: &'static str = T::NAME;
+    const SHAPE: Shape = Shape { arity: Arity::Many, category: T::CATEGORY };
+
+    fn from_capture(cap: Capture<'_>) -> Result<Vec<T>, Token<'_>> {
+        match cap {
+            Capture::Many(toks) => toks.into_iter()
+                .map(|tok| T::from_token(tok.text).ok_or(tok))
+                .collect(),
+            _ => panic!("Vec field bound to a non-repeated placeholder"),
+        }
+    }
+
+    fn render(&self, f: &mut fmt::Formatter, sep: &str) -> fmt::Result {
+        let sep = if sep.is_empty() { " " } else { sep };
+        for (i, val) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(sep)?;
+            }
+
+            write!(f, "{}", val)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes values out according to a pattern, so that scanning the output
+/// with the same pattern recovers them.  Used by `input!` to implement
+/// `Display`.
+pub struct Render<'f, 'a, 'p> {
+    f: &'f mut fmt::Formatter<'a>,
+    segs: ::std::vec::IntoIter<Segment<&'p str>>,
+}
+
+impl <'f, 'a, 'p> Render<'f, 'a, 'p> {
+    pub fn new(f: &'f mut fmt::Formatter<'a>, patt: &'p str) -> Render<'f, 'a, 'p> {
+        Render { f, segs: segments(patt).into_iter() }
+    }
+
+    /// Writes literal text up to the next placeholder, returning that
+    /// placeholder's repetition, or `None` if the pattern is exhausted.
+    fn literals(&mut self) -> Result<Option<Rep<&'p str>>, fmt::Error> {
+        for seg in &mut self.segs {
+            match seg {
+                Segment::Lit(lit) => self.f.write_str(lit)?,
+                Segment::Hole(_, rep) => return Ok(Some(rep)),
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Writes `val` in place of the next placeholder.
+    pub fn field<F: Field>(&mut self, val: &F) -> fmt::Result {
+        match self.literals()? {
+            Some(Rep::Many(sep)) => val.render(self.f, sep),
+            Some(_) => val.render(self.f, ""),
+            None => panic!("More fields than placeholders in pattern"),
+        }
+    }
+
+    /// Writes the rest of the pattern, followed by `suffix`.
+    pub fn finish(mut self, suffix: &str) -> fmt::Result {
+        if self.literals()?.is_some() {
+            panic!("More placeholders in pattern than fields");
+        }
+
+        self.f.write_str(suffix)
+    }
+}
+
+/// An error from parsing a line of input into a type declared by `input!`.
+/// Its details are boxed, to keep the `Result`s it is returned in small.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseError(Box<ParseErrorDetails>);
+
+/// What went wrong, and where, for a `ParseError`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseErrorDetails {
+    /// Name of the type being parsed.
+    pub ty: &'static str,
+    /// Name of the enum variant being parsed, if `ty` is an enum.
+    pub variant: Option<&'static str>,
+    /// Name of the field being parsed, if the failure was in a placeholder.
+    pub field: Option<&'static str>,
+    pub expected: Expected,
+    /// The text found where the expected text should have been.
+    pub found: String,
+    /// Byte offset into `input` at which parsing failed.
+    pub offset: usize,
+    /// The line that failed to parse.
+    pub input: String,
+    /// The (1-based) number of the line, if provided by the caller.
+    pub line: Option<usize>,
+    /// The file the line came from, if provided by the caller.
+    pub file: Option<String>,
+    /// If `ty` is an enum, the reason each of its variants failed to parse,
+    /// in declaration order.  The rest of the error describes the variant
+    /// that got furthest.
+    pub variants: Vec<ParseError>,
+}
+
+impl ParseError {
+    fn new(ty: &'static str, input: &str, offset: usize, expected: Expected) -> ParseError {
+        let rest = &input[offset..];
+        let found = match expected {
+            Expected::Suffix(_) | Expected::End | Expected::RecordEnd | Expected::Variant => rest,
+            _ => rest.split_whitespace().next().unwrap_or(""),
+        };
+
+        ParseError(Box::new(ParseErrorDetails {
+            ty, variant: None, field: None, expected, offset,
+            found: found.to_owned(),
+            input: input.to_owned(),
+            line: None,
+            file: None,
+            variants: vec![],
+        }))
+    }
+
+    /// Error for when `input` failed to match the pattern for `ty`, whose
+    /// fields are named (in order) by `fields`.
+    pub fn from_failure(
+        ty: &'static str,
+        fields: &[&'static str],
+        input: &str,
+        fail: Failure,
+    ) -> ParseError {
+        let Failure { hole, offset, expected } = fail;
+        let field = match expected {
+            Expected::Literal(_) => None,
+            _ => fields.get(hole).cloned(),
+        };
+
+        let mut err = ParseError::new(ty, input, offset, expected);
+        err.0.field = field;
+        err
+    }
+
+    /// Error for when `tok`, found at byte offset `offset` into `input`,
+    /// could not be converted into a value of type `value` for field `field`
+    /// of `ty`.
+    pub fn bad_value(
+        ty: &'static str,
+        field: &'static str,
+        value: &'static str,
+        input: &str,
+        offset: usize,
+        tok: &str,
+    ) -> ParseError {
+        let mut err = ParseError::new(ty, input, offset, Expected::Value(value));
+        err.0.field = Some(field);
+        err.0.found = tok.to_owned();
+        err
+    }
+
+    /// Error for when `input`, whose pattern matched up to byte offset `end`,
+    /// does not end with `suffix`.
+    pub fn bad_suffix(ty: &'static str, input: &str, end: usize, suffix: &str) -> ParseError {
+        let expected = Expected::Suffix(suffix.to_owned());
+        ParseError::new(ty, input, end, expected)
+    }
+
+    /// Error for when a multi-line record ran out of lines before reaching the
+    /// one for field `field` of `ty`.
+    pub fn missing_line(ty: &'static str, field: &'static str) -> ParseError {
+        let mut err = ParseError::new(ty, "", 0, Expected::Line);
+        err.0.field = Some(field);
+        err
+    }
+
+    /// Error for when `input` follows the last line of a multi-line record of
+    /// type `ty`.
+    pub fn extra_line(ty: &'static str, input: &str) -> ParseError {
+        ParseError::new(ty, input, 0, Expected::RecordEnd)
+    }
+
+    /// Error for when the line to be parsed into `ty` could not be read.
+    pub fn io(ty: &'static str, err: &io::Error) -> ParseError {
+        ParseError::new(ty, "", 0, Expected::Io(err.to_string()))
+    }
+
+    /// Error for when `input` matches none of the variants of enum `ty`.
+    /// `failures` pairs the name of each variant with the reason it failed.
+    pub fn no_variant(
+        ty: &'static str,
+        input: &str,
+        failures: Vec<(&'static str, ParseError)>,
+    ) -> ParseError {
+        let variants: Vec<_> = failures.into_iter()
+            .map(|(variant, mut err)| {
+                err.0.variant = Some(variant);
+                err
+            })
+            .collect();
+
+        // Prefer the earliest declared variant, if several got equally far.
+        let mut likeliest: Option<&ParseError> = None;
+        for err in &variants {
+            if likeliest.is_none_or(|l| l.progress() < err.progress()) {
+                likeliest = Some(err);
+            }
+        }
+
+        match likeliest.cloned() {
+            Some(mut err) => {
+                err.0.variants = variants;
+                err
+            },
+
+            None => ParseError::new(ty, input, 0, Expected::Variant),
+        }
+    }
+
+    /// How far into the input parsing got before failing.  A line that
+    /// matched its pattern but not its suffix gets credit for the prefix of
+    /// the suffix that it does match.
+    fn progress(&self) -> usize {
+        match &self.expected {
+            Expected::Suffix(suffix) => {
+                let common = self.found.chars()
+                    .zip(suffix.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a.len_utf8())
+                    .sum::<usize>();
+
+                self.offset + common
+            },
+
+            _ => self.offset,
+        }
+    }
+
+    /// Attributes the error to line number `line` (1-based).
+    pub fn on_line(mut self, line: usize) -> ParseError {
+        self.0.line = Some(line);
+        self
+    }
+
+    /// Attributes the error to the file called `file`.
+    pub fn in_file<F: Into<String>>(mut self, file: F) -> ParseError {
+        self.0.file = Some(file.into());
+        self
+    }
+
+    /// The (1-based) column, in characters, at which parsing failed.
+    pub fn column(&self) -> usize {
+        self.input[..self.offset].chars().count() + 1
+    }
+}
+
+impl Deref for ParseError {
+    type Target = ParseErrorDetails;
+
+    fn deref(&self) -> &ParseErrorDetails {
+        &self.0
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expected::Integer => write!(f, "integer"),
+            Expected::Token => write!(f, "token"),
+            Expected::Class(set) => write!(f, "one of [{}]", set),
+            Expected::Literal(lit) => write!(f, "{:?}", lit),
+            Expected::Suffix(suff) => write!(f, "suffix {:?}", suff),
+            Expected::Value(ty) => write!(f, "{}", ty),
+            Expected::Variant => write!(f, "a variant"),
+            Expected::End => write!(f, "end of line"),
+            Expected::Line => write!(f, "a line"),
+            Expected::RecordEnd => write!(f, "end of record"),
+            Expected::Io(_) => write!(f, "a readable line"),
+        }
+    }
+}
+
+impl ParseError {
+    /// Writes the variant and field that failed, and why, e.g.
+    /// `Wake.min expected integer, got "x"`.
+    fn fmt_reason(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.variant, self.field) {
+            (Some(variant), Some(field)) => write!(f, "{}.{} ", variant, field)?,
+            (Some(name), None) | (None, Some(name)) => write!(f, "{} ", name)?,
+            (None, None) => {},
+        }
+
+        if let Expected::Io(err) = &self.expected {
+            return write!(f, "could not be read: {}", err);
+        }
+
+        match self.found.as_str() {
+            "" if self.expected == Expected::Line => {
+                write!(f, "expected {}, got end of record", self.expected)
+            },
+
+            "" => write!(f, "expected {}, got end of line", self.expected),
+            found => write!(f, "expected {}, got {:?}", self.expected, found),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+        }
+
+        write!(f, "{}: ", self.column())?;
+        if self.variants.is_empty() {
+            match (self.variant, self.field) {
+                (Some(_), _) => write!(f, "{}::", self.ty)?,
+                (None, Some(_)) => write!(f, "{}.", self.ty)?,
+                (None, None) => write!(f, "{} ", self.ty)?,
+            }
+
+            return self.fmt_reason(f);
+        }
+
+        write!(f, "{} matched no variant, most likely ", self.ty)?;
+        self.fmt_reason(f)?;
+
+        for err in &self.variants {
+            write!(f, "\n    column {}: ", err.column())?;
+            err.fmt_reason(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors are formatted the same way for debugging as for display, so that
+/// they read well when propagated out of `main`.
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<ParseError> for io::Error {
+    fn from(err: ParseError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Every error from parsing some input, in the order they occurred.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseErrors(pub Vec<ParseError>);
+
+/// Each error is written on its own line.
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, err) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", err)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Error for ParseErrors {}
+
+impl From<ParseErrors> for io::Error {
+    fn from(errs: ParseErrors) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidInput, errs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use scan::{check_field, count_holes, scan, scan_line};
+    use scan::{Arity, Capture, Category, Expected, Failure, Mismatch, Mode, ParseError, Shape, Token};
+    use self::Text::*;
+
+    /// A `Capture` without the offsets of its tokens.
+    #[derive(Debug, PartialEq, Eq)]
+    enum Text<'s> {
+        One(&'s str),
+        Maybe(Option<&'s str>),
+        Many(Vec<&'s str>),
+    }
+
+    fn texts(caps: Vec<Capture<'_>>) -> Vec<Text<'_>> {
+        caps.into_iter()
+            .map(|cap| match cap {
+                Capture::One(tok) => One(tok.text),
+                Capture::Maybe(tok) => Maybe(tok.map(|t| t.text)),
+                Capture::Many(toks) => Many(toks.iter().map(|t| t.text).collect()),
+            })
+            .collect()
+    }
+
+    fn ok<'s>(input: &'s str, patt: &str) -> Vec<Text<'s>> {
+        texts(scan(input, patt).expect("scan failed").caps)
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(
+            ok("#1 @ 12,-3: 4x+5", "#{d} @ {d},{d}: {d}x{d}"),
+            [One("1"), One("12"), One("-3"), One("4"), One("+5")],
+        );
+
+        assert_eq!(
+            ok("Step C must be finished before step A can begin.",
+               "Step {} must be finished before step {}"),
+            [One("C"), One("A")],
+        );
+
+        assert_eq!(ok("..#.# => #", "{} => {[.#]}"), [One("..#.#"), One("#")]);
+    }
+
+    #[test]
+    fn token_stops_at_literal() {
+        assert_eq!(ok("abc,def", "{},{}"), [One("abc"), One("def")]);
+        assert_eq!(ok("a-b-x", "{}-x"), [One("a-b")]);
+    }
+
+    #[test]
+    fn token_spans_partial_literal() {
+        // Tokens only stop where all of the following literal matches.
+        assert_eq!(ok("jump must go", "{} must go"), [One("jump")]);
+        assert_eq!(ok("a-b -> 1", "{} -> {d*, }"), [One("a-b"), Many(vec!["1"])]);
+        assert_eq!(ok("band and sand and x", "{* and }"), [Many(vec!["band", "sand", "x"])]);
+        assert_eq!(ok("a,,b,c", "{*,,}"), [Many(vec!["a", "b,c"])]);
+    }
+
+    #[test]
+    fn offsets() {
+        let tok = |text, offset| Token { text, offset };
+        assert_eq!(
+            scan(" 12 ab [3] 4,56", "{d} {} [{d?}] {d*,}").unwrap().caps,
+            [
+                Capture::One(tok("12", 1)),
+                Capture::One(tok("ab", 4)),
+                Capture::Maybe(Some(tok("3", 8))),
+                Capture::Many(vec![tok("4", 11), tok("56", 13)]),
+            ],
+        );
+    }
+
+    #[test]
+    fn whitespace_insignificant() {
+        assert_eq!(ok("  <1,2>", "< {d} , {d} >"), [One("1"), One("2")]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(ok("{42}", "{{{d}}}"), [One("42")]);
+    }
+
+    #[test]
+    fn checks() {
+        let patt = "#{d}: {[a-z]?} {*, } {d}x{d}";
+        let int = |arity| Shape { arity, category: Category::Integer };
+        let other = |arity| Shape { arity, category: Category::Other };
+
+        assert_eq!(count_holes(patt), 5);
+        assert_eq!(count_holes("{{}} {{{d}}}"), 1);
+
+        assert_eq!(check_field(patt, 0, int(Arity::One)), Ok(()));
+        assert_eq!(check_field(patt, 0, other(Arity::One)), Err(Mismatch::NotInteger));
+        assert_eq!(check_field(patt, 0, int(Arity::Many)), Err(Mismatch::Arity));
+        assert_eq!(check_field(patt, 1, other(Arity::Maybe)), Ok(()));
+        assert_eq!(check_field(patt, 1, int(Arity::One)), Err(Mismatch::Arity));
+        assert_eq!(check_field(patt, 2, other(Arity::Many)), Ok(()));
+        assert_eq!(check_field(patt, 2, int(Arity::Many)), Ok(()));
+        assert_eq!(check_field(patt, 4, int(Arity::One)), Ok(()));
+        assert_eq!(check_field(patt, 5, int(Arity::One)), Err(Mismatch::Missing));
+    }
+
+    #[test]
+    #[should_panic(expected = "Malformed placeholder")]
+    fn malformed() {
+        count_holes("{x}");
+    }
+
+    #[test]
+    fn failures() {
+        assert_eq!(
+            scan("#x @ 1,2", "#{d} @ {d},{d}"),
+            Err(Failure { hole: 0, offset: 1, expected: Expected::Integer }),
+        );
+
+        assert_eq!(
+            scan("#1 @ 1;2", "#{d} @ {d},{d}"),
+            Err(Failure { hole: 2, offset: 6, expected: Expected::Literal(",".to_owned()) }),
+        );
+
+        assert_eq!(
+            scan("[1]", "[{d}]]"),
+            Err(Failure { hole: 1, offset: 3, expected: Expected::Literal("]".to_owned()) }),
+        );
+    }
+
+    #[test]
+    fn error_messages() {
+        let patt = "#{d} @ {d},{d}: {d}x{d}";
+        let fields = ["id", "left", "top", "width", "height"];
+        let fail = |input| {
+            let f = scan(input, patt).unwrap_err();
+            ParseError::from_failure("Rect", &fields, input, f)
+        };
+
+        assert_eq!(
+            fail("#1 @ 1,3: x3x4").in_file("input.txt").on_line(17).to_string(),
+            "input.txt:17:11: Rect.width expected integer, got \"x3x4\"",
+        );
+
+        assert_eq!(
+            fail("#1 @ 1,3 4x4").on_line(2).to_string(),
+            "2:10: Rect expected \":\", got \"4x4\"",
+        );
+
+        assert_eq!(
+            fail("#1 @ 1,3: 4x").to_string(),
+            "13: Rect.height expected integer, got end of line",
+        );
+
+        let input = "#1 @ 1,3: 4x300";
+        let err = ParseError::bad_value("Rect", "height", "u8", input, 12, "300");
+        assert_eq!(err.to_string(), "13: Rect.height expected u8, got \"300\"");
+
+        let err = ParseError::bad_suffix("Game", "9 players; 25 point", 13, " points");
+        assert_eq!(err.to_string(), "14: Game expected suffix \" points\", got \" point\"");
+    }
+
+    #[test]
+    fn variant_errors() {
+        let input = "[11-01 00:05] falls aslep";
+        let fail = |patt| {
+            let f = scan(input, patt).unwrap_err();
+            ParseError::from_failure("Log", &["m", "d", "hr", "min", "id"], input, f)
+        };
+
+        let shift = fail("[{d}-{d} {d}:{d}] Guard #{d}");
+        let wake = ParseError::bad_suffix("Log", input, 13, " wakes up");
+        let sleep = ParseError::bad_suffix("Log", input, 13, " falls asleep");
+
+        let err = ParseError::no_variant("Log", input, vec![
+            ("Shift", shift),
+            ("Wake", wake),
+            ("Sleep", sleep),
+        ]).on_line(4);
+
+        assert_eq!(err.variant, Some("Sleep"));
+        assert_eq!(err.variants.len(), 3);
+        assert_eq!(
+            err.to_string(),
+            "4:14: Log matched no variant, most likely \
+             Sleep expected suffix \" falls asleep\", got \" falls aslep\"\n    \
+             column 15: Shift expected \"Guard #\", got \"falls\"\n    \
+             column 14: Wake expected suffix \" wakes up\", got \" falls aslep\"\n    \
+             column 14: Sleep expected suffix \" falls asleep\", got \" falls aslep\"",
+        );
+    }
+
+    #[test]
+    fn variant_ties() {
+        let input = "x";
+        let err = ParseError::no_variant("E", input, vec![
+            ("A", ParseError::from_failure("E", &["a"], input, scan(input, "{d}").unwrap_err())),
+            ("B", ParseError::from_failure("E", &["b"], input, scan(input, "{d}").unwrap_err())),
+        ]);
+
+        assert_eq!(err.variant, Some("A"));
+        assert_eq!(err.field, Some("a"));
+    }
+
+    #[test]
+    fn optional() {
+        assert_eq!(ok("a 1", "a {d?}"), [Maybe(Some("1"))]);
+        assert_eq!(ok("a b", "a {d?} b"), [Maybe(None)]);
+    }
+
+    #[test]
+    fn repeated() {
+        assert_eq!(ok("2 3 0 3 10", "{d*}"), [Many(vec!["2", "3", "0", "3", "10"])]);
+        assert_eq!(ok("[]", "[{d*, }]"), [Many(vec![])]);
+        assert_eq!(
+            ok("[1, 2,3] x", "[{d*, }] {}"),
+            [Many(vec!["1", "2", "3"]), One("x")],
+        );
+
+        assert_eq!(
+            ok("a,b, c;", "{*,};"),
+            [Many(vec!["a", "b", "c"])],
+        );
+    }
+
+    #[test]
+    fn modes() {
+        let patt = "#{d} @ {d},{d}: {d}x{d}";
+        let line = |input, mode| scan_line(input, patt, "", mode).map(|m| m.caps.len());
+        let fail = |offset, expected| Err(Failure { hole: 5, offset, expected });
+
+        assert_eq!(line("#1 @ 1,3: 4x4 junk", Mode::Loose), Ok(5));
+        assert_eq!(line("# 1 @ 1 , 3 :4x4", Mode::Loose), Ok(5));
+
+        assert_eq!(line("#1 @ 1,3: 4x4", Mode::Strict), Ok(5));
+        assert_eq!(line("#1 @ 1,3: 4x4 junk", Mode::Strict), fail(13, Expected::End));
+        assert_eq!(line("#1 @ 1,3: 4x4\r", Mode::Strict), fail(13, Expected::End));
+        assert_eq!(
+            line("#1 @ 1,3:4x4", Mode::Strict),
+            Err(Failure { hole: 3, offset: 9, expected: Expected::Literal(" ".to_owned()) }),
+        );
+
+        assert_eq!(line("  #1  @ 1,3:\t4x4 \r", Mode::Lenient), Ok(5));
+        assert_eq!(line("#1 @ 1,3: 4x4 junk", Mode::Lenient), fail(14, Expected::End));
+        assert_eq!(
+            line("#1 @ 1 ,3: 4x4", Mode::Lenient),
+            Err(Failure { hole: 2, offset: 6, expected: Expected::Literal(",".to_owned()) }),
+        );
+    }
+
+    #[test]
+    fn suffixes() {
+        let line = |input, mode| scan_line(input, "{d} {d*}", " pushed", mode).map(|m| texts(m.caps));
+        let pushed = Ok(vec![One("1"), Many(vec!["2", "3"])]);
+
+        assert_eq!(line("1 2 3 pushed", Mode::Strict), pushed);
+        assert_eq!(line("1 2  3  pushed\r\n", Mode::Lenient), pushed);
+        assert_eq!(line("1 2 3 pushed", Mode::Loose), pushed);
+        assert_eq!(line("1 2 3 x pushed", Mode::Loose), pushed);
+
+        assert_eq!(
+            line("1 2 3 x pushed", Mode::Strict),
+            Err(Failure { hole: 2, offset: 5, expected: Expected::Suffix(" pushed".to_owned()) }),
+        );
+
+        let input = "1 2 3 pushed!";
+        let err = scan_line(input, "{d} {d*}", " pushed", Mode::Strict).unwrap_err();
+        assert_eq!(
+            ParseError::from_failure("Push", &["n", "rest"], input, err).to_string(),
+            "13: Push expected end of line, got \"!\"",
+        );
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(ok("ab-c", "{[a-c]}"), [One("ab")]);
+        assert_eq!(ok("ab-c", "{[-a-c]}"), [One("ab-c")]);
+        assert_eq!(ok("ab,c", "{[^,]}"), [One("ab")]);
+        assert_eq!(ok("]]x", "{[]]}"), [One("]]")]);
+    }
+}
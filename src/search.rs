@@ -0,0 +1,311 @@
+//! Breadth-first search over graphs given by a neighbour function, such as
+//! the open cells of a grid.
+//!
+//! [`Bfs`] explores lazily, in order of distance from one or more starting
+//! nodes, remembering the distance to every node it reaches.  From these it
+//! can find the nearest node matching a predicate, and reconstruct shortest
+//! paths.  Ties between equally near nodes, or equally short paths, are broken
+//! by a key function: [`reading_order`] gives the order most puzzles ask for
+//! on grids.
+//!
+//! [`Bfs`]: struct.Bfs.html
+//! [`reading_order`]: fn.reading_order.html
+
+use grid::Grid;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Key ordering `(x, y)` co-ordinates in reading order: top to bottom, then
+/// left to right.
+pub fn reading_order<T: Copy>(&(x, y): &(T, T)) -> (T, T) {
+    (y, x)
+}
+
+/// What the search knows about a node it has reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Visit {
+    /// Length of the shortest path to the node from any start.
+    pub dist: usize,
+    /// Index of the start nearest to the node, or `None` if several starts
+    /// are equally near.
+    pub origin: Option<usize>,
+}
+
+/// A breadth-first search, from one or more starting nodes, of the graph
+/// whose edges are given by `neighbours`.
+///
+/// As an iterator, it yields each node it explores alongside its distance
+/// from the nearest start, in order of distance.
+pub struct Bfs<N, F> {
+    neighbours: F,
+    frontier: VecDeque<N>,
+    visits: HashMap<N, Visit>,
+    /// Nodes in the order they were explored, and so in order of distance.
+    explored: Vec<N>,
+}
+
+impl <N, F, I> Bfs<N, F>
+    where N: Copy + Eq + Hash,
+          F: FnMut(N) -> I,
+          I: IntoIterator<Item = N>
+{
+    pub fn new(start: N, neighbours: F) -> Bfs<N, F> {
+        Bfs::multi(Some(start), neighbours)
+    }
+
+    /// Searches outwards from all of `starts` at once, so that each node is
+    /// reached from the start nearest to it.  Nodes that appear more than
+    /// once in `starts` belong to their first appearance.
+    pub fn multi<S>(starts: S, neighbours: F) -> Bfs<N, F>
+        where S: IntoIterator<Item = N>
+    {
+        let mut frontier = VecDeque::new();
+        let mut visits = HashMap::new();
+        for (i, start) in starts.into_iter().enumerate() {
+            visits.entry(start).or_insert_with(|| {
+                frontier.push_back(start);
+                Visit { dist: 0, origin: Some(i) }
+            });
+        }
+
+        Bfs { neighbours, frontier, visits, explored: vec![] }
+    }
+
+    /// Explores the rest of the graph.
+    pub fn run(mut self) -> Bfs<N, F> {
+        while self.next().is_some() {}
+        self
+    }
+
+    /// What is known about `node`, if the search has reached it.
+    pub fn visit(&self, node: &N) -> Option<Visit> {
+        self.visits.get(node).cloned()
+    }
+
+    pub fn dist(&self, node: &N) -> Option<usize> {
+        self.visit(node).map(|v| v.dist)
+    }
+
+    /// Nodes explored so far, in order of distance.
+    pub fn explored(&self) -> &[N] {
+        &self.explored
+    }
+
+    /// The nearest node satisfying `pred`, exploring only as far as needed to
+    /// find it.  Ties go to the node with the smallest `key`.
+    pub fn nearest<P, K, O>(&mut self, mut pred: P, mut key: K) -> Option<N>
+        where P: FnMut(&N) -> bool,
+              K: FnMut(&N) -> O,
+              O: Ord
+    {
+        let mut best: Option<(N, usize)> = None;
+        for i in 0 .. {
+            if i == self.explored.len() && self.next().is_none() {
+                break;
+            }
+
+            let node = self.explored[i];
+            let dist = self.visits[&node].dist;
+            if let Some((_, bdist)) = best {
+                // Every node after this one is further away.
+                if dist > bdist {
+                    break;
+                }
+            }
+
+            if pred(&node) && best.is_none_or(|(bnode, _)| key(&node) < key(&bnode)) {
+                best = Some((node, dist));
+            }
+        }
+
+        best.map(|(node, _)| node)
+    }
+
+    /// A shortest path from a start to `target`, including both, or `None` if
+    /// the search does not reach `target`.  Ties between paths go to the one
+    /// whose first node with a different `key` has the smaller `key`: with
+    /// `reading_order`, the path whose first step is first in reading order.
+    pub fn path_to<K, O>(&mut self, target: N, mut key: K) -> Option<Vec<N>>
+        where K: FnMut(&N) -> O,
+              O: Ord
+    {
+        // Every node that could come before `target` on a path must have
+        // been explored.
+        while self.visit(&target).is_none() || self.frontier.front().is_some_and(|n| {
+            self.visits[n].dist < self.visits[&target].dist
+        }) {
+            self.next()?;
+        }
+
+        let Bfs { neighbours, visits, explored, .. } = self;
+        let len = visits[&target].dist;
+
+        // Nodes on some shortest path to `target`, by distance, found by
+        // working backwards from it.
+        let mut layers = vec![HashSet::new(); len + 1];
+        layers[len].insert(target);
+        for &node in explored.iter().rev() {
+            let dist = visits[&node].dist;
+            if dist < len && neighbours(node).into_iter().any(|n| layers[dist + 1].contains(&n)) {
+                layers[dist].insert(node);
+            }
+        }
+
+        // Follow the preferred path forwards.
+        let mut node = *layers[0].iter().min_by_key(|n| key(n))?;
+        let mut path = vec![node];
+        for layer in &layers[1 ..] {
+            node = neighbours(node).into_iter()
+                .filter(|n| layer.contains(n))
+                .min_by_key(|n| key(n))
+                .expect("Path must continue to the next layer");
+            path.push(node);
+        }
+
+        Some(path)
+    }
+}
+
+impl <F> Bfs<(usize, usize), F> {
+    /// Distances to each cell of a `width` by `height` grid, or `None` for
+    /// cells the search has not reached.
+    pub fn distance_grid(&self, width: usize, height: usize) -> Grid<Option<usize>> {
+        Grid::new_with_mapping(width, height, |x, y| self.visits.get(&(x, y)).map(|v| v.dist))
+    }
+}
+
+impl <N, F, I> Iterator for Bfs<N, F>
+    where N: Copy + Eq + Hash,
+          F: FnMut(N) -> I,
+          I: IntoIterator<Item = N>
+{
+    type Item = (N, usize);
+
+    fn next(&mut self) -> Option<(N, usize)> {
+        let node = self.frontier.pop_front()?;
+        let Visit { dist, origin } = self.visits[&node];
+
+        for nbr in (self.neighbours)(node) {
+            match self.visits.get_mut(&nbr) {
+                None => {
+                    self.visits.insert(nbr, Visit { dist: dist + 1, origin });
+                    self.frontier.push_back(nbr);
+                },
+
+                // Reached at the same distance from a different start.
+                Some(visit) => if visit.dist == dist + 1 && visit.origin != origin {
+                    visit.origin = None;
+                },
+            }
+        }
+
+        self.explored.push(node);
+        Some((node, dist))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use grid::Grid;
+    use search::{reading_order, Bfs, Visit};
+
+    /// Open cells of a maze, where `#` is a wall.
+    fn maze(rows: &[&str]) -> Grid<bool> {
+        Grid::parse(&rows.join("\n"), |_, _, b| Some(b != b'#')).unwrap()
+    }
+
+    fn open(grid: &Grid<bool>) -> impl Fn((usize, usize)) -> Vec<(usize, usize)> + '_ {
+        move |pos| grid.neighbours4(pos).filter(|&n| grid[n]).collect()
+    }
+
+    #[test]
+    fn distances() {
+        let grid = maze(&[
+            "...#",
+            ".#..",
+            "...#",
+        ]);
+
+        let bfs = Bfs::new((0, 0), open(&grid));
+        let order: Vec<usize> = bfs.map(|(_, d)| d).collect();
+        assert_eq!(order, [0, 1, 1, 2, 2, 3, 3, 4, 4]);
+
+        let bfs = Bfs::new((0, 0), open(&grid)).run();
+        let dists = bfs.distance_grid(4, 3);
+        assert_eq!(dists[(2, 1)], Some(3));
+        assert_eq!(dists[(3, 1)], Some(4));
+        assert_eq!(dists[(1, 1)], None);
+        assert_eq!(dists[(3, 2)], None);
+    }
+
+    #[test]
+    fn multi_source() {
+        let grid = maze(&["....."]);
+        let bfs = Bfs::multi(vec![(0, 0), (3, 0), (0, 0)], open(&grid)).run();
+
+        let visit = |x| bfs.visit(&(x, 0)).unwrap();
+        assert_eq!(visit(0), Visit { dist: 0, origin: Some(0) });
+        assert_eq!(visit(1), Visit { dist: 1, origin: Some(0) });
+        assert_eq!(visit(2), Visit { dist: 1, origin: Some(1) });
+        assert_eq!(visit(4), Visit { dist: 1, origin: Some(1) });
+
+        // Equally near both starts, and so are the cells beyond it.
+        let grid = maze(&["...", "..."]);
+        let bfs = Bfs::multi(vec![(0, 0), (2, 0)], open(&grid)).run();
+        assert_eq!(bfs.visit(&(1, 0)).unwrap().origin, None);
+        assert_eq!(bfs.visit(&(1, 1)).unwrap().origin, None);
+        assert_eq!(bfs.visit(&(0, 1)).unwrap().origin, Some(0));
+    }
+
+    #[test]
+    fn nearest() {
+        let grid = maze(&[
+            ".....",
+            ".....",
+            ".....",
+        ]);
+
+        let targets = [(4, 0), (0, 2), (3, 1), (4, 2)];
+        let mut bfs = Bfs::new((1, 1), open(&grid));
+        let target = bfs.nearest(|n| targets.contains(n), reading_order);
+        assert_eq!(target, Some((3, 1)));
+        assert_eq!(bfs.dist(&(3, 1)), Some(2));
+
+        // Only explored as far as it needed to.
+        assert!(bfs.explored().len() < 15);
+
+        // Nodes that were already explored are still candidates.
+        assert_eq!(bfs.nearest(|&n| n == (1, 1), reading_order), Some((1, 1)));
+        assert_eq!(bfs.nearest(|&n| n == (9, 9), reading_order), None);
+    }
+
+    #[test]
+    fn paths() {
+        let grid = maze(&[
+            ".......",
+            ".#.#.#.",
+            ".......",
+        ]);
+
+        let mut bfs = Bfs::new((2, 1), open(&grid));
+        assert_eq!(
+            bfs.path_to((4, 1), reading_order),
+            Some(vec![(2, 1), (2, 0), (3, 0), (4, 0), (4, 1)]),
+        );
+
+        let mut bfs = Bfs::new((0, 0), open(&grid));
+        let path = bfs.path_to((2, 2), reading_order).unwrap();
+        assert_eq!(path, [(0, 0), (1, 0), (2, 0), (2, 1), (2, 2)]);
+
+        // Prefer cells further left instead.
+        let path = bfs.path_to((2, 2), |&(x, y)| (x, y)).unwrap();
+        assert_eq!(path, [(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]);
+
+        assert_eq!(bfs.path_to((0, 0), reading_order), Some(vec![(0, 0)]));
+        assert_eq!(bfs.path_to((1, 1), reading_order), None);
+
+        // From the nearest of several starts.
+        let mut bfs = Bfs::multi(vec![(6, 2), (0, 2)], open(&grid));
+        assert_eq!(bfs.path_to((1, 2), reading_order), Some(vec![(0, 2), (1, 2)]));
+    }
+}
@@ -0,0 +1,213 @@
+//! Iterator adaptors that look at several consecutive elements at once.
+//!
+//! Unlike the `windows` method on slices, these work on any iterator, reading
+//! from it only as far as they need to.
+
+use std::collections::VecDeque;
+
+/// An iterator over every run of `size` consecutive elements of its
+/// underlying iterator, as a `Vec`.
+///
+/// Constructed by a call to [`windows`].
+///
+/// [`windows`]: trait.Sliding.html#method.windows
+pub struct Windows<I: Iterator> {
+    iter: I,
+    size: usize,
+    window: VecDeque<I::Item>,
+}
+
+impl <I> Iterator for Windows<I>
+    where I: Iterator,
+          I::Item: Clone
+{
+    type Item = Vec<I::Item>;
+
+    fn next(&mut self) -> Option<Vec<I::Item>> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+
+        Some(self.window.iter().cloned().collect())
+    }
+}
+
+/// An iterator over each pair of consecutive elements of its underlying
+/// iterator.
+///
+/// Constructed by a call to [`pairs`].
+///
+/// [`pairs`]: trait.Sliding.html#method.pairs
+pub struct Pairs<I: Iterator> {
+    iter: I,
+    prev: Option<I::Item>,
+}
+
+impl <I> Iterator for Pairs<I>
+    where I: Iterator,
+          I::Item: Clone
+{
+    type Item = (I::Item, I::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.prev.is_none() {
+            self.prev = Some(self.iter.next()?);
+        }
+
+        let next = self.iter.next()?;
+        let prev = self.prev.replace(next.clone());
+        prev.map(|prev| (prev, next))
+    }
+}
+
+/// An iterator over the offsets in its underlying iterator at which a pattern
+/// starts, including matches that overlap each other.
+///
+/// Constructed by a call to [`subsequence_offsets`].
+///
+/// [`subsequence_offsets`]: trait.Sliding.html#method.subsequence_offsets
+pub struct Matches<I: Iterator> {
+    iter: I,
+    pattern: Vec<I::Item>,
+    /// `fallback[i]` is the length of the longest proper prefix of
+    /// `pattern[..= i]` that is also a suffix of it: how much of the pattern
+    /// is still matched when a match of `i + 1` elements fails to continue.
+    fallback: Vec<usize>,
+    /// Length of the prefix of the pattern matched so far.
+    matched: usize,
+    /// Number of elements read from the underlying iterator.
+    read: usize,
+}
+
+impl <I> Matches<I>
+    where I: Iterator,
+          I::Item: PartialEq
+{
+    fn new(iter: I, pattern: Vec<I::Item>) -> Matches<I> {
+        assert!(!pattern.is_empty(), "Cannot search for an empty pattern");
+
+        let mut fallback = vec![0; pattern.len()];
+        let mut k = 0;
+        for i in 1 .. pattern.len() {
+            while k > 0 && pattern[i] != pattern[k] {
+                k = fallback[k - 1];
+            }
+
+            if pattern[i] == pattern[k] {
+                k += 1;
+            }
+
+            fallback[i] = k;
+        }
+
+        Matches { iter, pattern, fallback, matched: 0, read: 0 }
+    }
+}
+
+impl <I> Iterator for Matches<I>
+    where I: Iterator,
+          I::Item: PartialEq
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let Matches { iter, pattern, fallback, matched, read } = self;
+        for item in iter {
+            *read += 1;
+
+            while *matched > 0 && pattern[*matched] != item {
+                *matched = fallback[*matched - 1];
+            }
+
+            if pattern[*matched] == item {
+                *matched += 1;
+            }
+
+            if *matched == pattern.len() {
+                *matched = fallback[*matched - 1];
+                return Some(*read - pattern.len());
+            }
+        }
+
+        None
+    }
+}
+
+/// Extension trait for iterators, to look at consecutive elements together.
+pub trait Sliding
+    where Self: Iterator + Sized
+{
+    /// Creates an iterator over every run of `size` consecutive elements.
+    /// Each is copied into a `Vec` of its own, so prefer `pairs` for runs of
+    /// two, and `subsequence_offsets` to search for a particular run.
+    ///
+    /// Panics if `size` is zero.
+    fn windows(self, size: usize) -> Windows<Self> {
+        assert!(size > 0, "Windows must not be empty");
+        Windows { iter: self, size, window: VecDeque::with_capacity(size) }
+    }
+
+    /// Creates an iterator over each element paired with the one after it.
+    fn pairs(self) -> Pairs<Self> {
+        Pairs { iter: self, prev: None }
+    }
+
+    /// Creates an iterator over the offsets at which each occurrence of
+    /// `pattern` starts, in a single pass that does not buffer any elements.
+    ///
+    /// Panics if `pattern` is empty.
+    fn subsequence_offsets<P>(self, pattern: P) -> Matches<Self>
+        where P: IntoIterator<Item = Self::Item>,
+              Self::Item: PartialEq
+    {
+        Matches::new(self, pattern.into_iter().collect())
+    }
+}
+
+/// All iterators can slide.
+impl <I> Sliding for I where I: Iterator {}
+
+#[cfg(test)]
+mod tests {
+    use sliding::Sliding;
+
+    #[test]
+    fn windows() {
+        let ws: Vec<Vec<usize>> = (1 .. 6).windows(3).collect();
+        assert_eq!(ws, [[1, 2, 3], [2, 3, 4], [3, 4, 5]]);
+
+        assert_eq!((1 .. 3).windows(3).count(), 0);
+        assert_eq!("abc".chars().windows(1).count(), 3);
+    }
+
+    #[test]
+    fn pairs() {
+        let diffs: Vec<i32> = [1, 4, 9, 16].iter().pairs().map(|(a, b)| b - a).collect();
+        assert_eq!(diffs, [3, 5, 7]);
+
+        assert_eq!((0 .. 1).pairs().next(), None);
+        assert_eq!((0 .. 0).pairs().next(), None);
+    }
+
+    #[test]
+    fn subsequences() {
+        let offsets: Vec<usize> = "abababcabab".chars().subsequence_offsets("abab".chars()).collect();
+        assert_eq!(offsets, [0, 2, 7]);
+
+        let offsets: Vec<usize> = "aaaa".chars().subsequence_offsets("aa".chars()).collect();
+        assert_eq!(offsets, [0, 1, 2]);
+
+        // Failed partial matches fall back to the longest prefix still
+        // matched.
+        let offsets: Vec<usize> = "aabaabaaab".bytes().subsequence_offsets(b"aaab".to_vec()).collect();
+        assert_eq!(offsets, [6]);
+
+        // Works on unbounded iterators.
+        let first = (0 ..).map(|n| n % 7).subsequence_offsets(vec![5, 6, 0]).next();
+        assert_eq!(first, Some(5));
+    }
+}